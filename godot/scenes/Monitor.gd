@@ -0,0 +1,9 @@
+extends SubViewport
+
+## Shows the security terminal on the office monitor
+
+func _ready() -> void:
+	GlobalTerminal.monitor_label = $Label
+
+func _exit_tree() -> void:
+	GlobalTerminal.monitor_label = null
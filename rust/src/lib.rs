@@ -10,6 +10,7 @@ use godot::{
 use tracing_error::ErrorLayer;
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
+mod palette;
 mod terminal;
 mod tracing_godot;
 
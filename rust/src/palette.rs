@@ -0,0 +1,138 @@
+use ratatui::style::Color;
+
+pub type Rgb = (u8, u8, u8);
+
+/// The 16 ANSI colors, in the order of their indexes
+pub const ANSI_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// How the log page, spectators and the in-game monitor show the
+/// 16 ANSI colors. Keep in sync with `screen.css`
+pub const LOG_PAGE: [Rgb; 16] = [
+    (0x01, 0x06, 0x0E),
+    (0xEA, 0x6C, 0x73),
+    (0x91, 0xB3, 0x62),
+    (0xF9, 0xAF, 0x4F),
+    (0x53, 0xBD, 0xFA),
+    (0xFA, 0xE9, 0x94),
+    (0x90, 0xE1, 0xC6),
+    (0xC7, 0xC7, 0xC7),
+    (0x68, 0x68, 0x68),
+    (0xF0, 0x71, 0x78),
+    (0xC2, 0xD9, 0x4C),
+    (0xFF, 0xB4, 0x54),
+    (0x59, 0xC2, 0xFF),
+    (0xFF, 0xEE, 0x99),
+    (0x95, 0xE6, 0xCB),
+    (0xFF, 0xFF, 0xFF),
+];
+/// The colors the log page draws cells that don't set their own in
+pub const LOG_PAGE_FOREGROUND: Rgb = (0xB3, 0xB1, 0xAD);
+pub const LOG_PAGE_BACKGROUND: Rgb = (0x0F, 0x14, 0x19);
+
+/// The 16 ANSI colors as xterm draws them
+pub const XTERM: [Rgb; 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The levels of each channel in the 256 color cube
+pub const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The index of one of the 16 ANSI colors, however it was named
+pub fn ansi_index(color: Color) -> Option<u8> {
+    match color {
+        Color::Indexed(index @ 0..=15) => Some(index),
+        color => ANSI_COLORS
+            .iter()
+            .position(|ansi| *ansi == color)
+            .map(|index| index as u8),
+    }
+}
+
+/// A color of the 256 color palette as RGB, with the 16 ANSI
+/// colors taken from `ansi`
+pub fn indexed_to_rgb(index: u8, ansi: &[Rgb; 16]) -> Rgb {
+    match index {
+        0..=15 => ansi[index as usize],
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// The color as RGB, with the 16 ANSI colors taken from `ansi`,
+/// or `None` for the default color
+pub fn to_rgb(color: Color, ansi: &[Rgb; 16]) -> Option<Rgb> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index) => Some(indexed_to_rgb(index, ansi)),
+        color => ansi_index(color).map(|index| ansi[index as usize]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use crate::palette::{LOG_PAGE, XTERM, ansi_index, indexed_to_rgb, to_rgb};
+
+    #[test]
+    fn names_and_indexes_agree() {
+        assert_eq!(ansi_index(Color::LightBlue), Some(12));
+        assert_eq!(ansi_index(Color::Indexed(12)), Some(12));
+        assert_eq!(ansi_index(Color::Indexed(16)), None);
+        assert_eq!(to_rgb(Color::LightBlue, &LOG_PAGE), Some(LOG_PAGE[12]));
+        assert_eq!(to_rgb(Color::Reset, &LOG_PAGE), None);
+    }
+
+    #[test]
+    fn maps_the_cube_and_gray_ramp() {
+        assert_eq!(indexed_to_rgb(1, &XTERM), XTERM[1]);
+        assert_eq!(indexed_to_rgb(16, &XTERM), (0, 0, 0));
+        assert_eq!(indexed_to_rgb(33, &XTERM), (0, 135, 255));
+        assert_eq!(indexed_to_rgb(231, &XTERM), (255, 255, 255));
+        assert_eq!(indexed_to_rgb(232, &XTERM), (8, 8, 8));
+        assert_eq!(indexed_to_rgb(255, &XTERM), (238, 238, 238));
+    }
+}
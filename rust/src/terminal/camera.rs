@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+use tachyonfx::{Effect, Interpolation, SimpleRng, fx};
+use tracing::instrument;
+
+/// Which camera feeds are currently disrupted, and for how
+/// many more seconds
+#[derive(Default)]
+pub struct CameraFeeds {
+    disrupted: HashMap<String, f64>,
+    rng: SimpleRng,
+}
+
+impl CameraFeeds {
+    /// Fills `view` with static for `seconds`. Disrupting a feed
+    /// that is already disrupted keeps the longer of the two
+    pub fn disrupt(&mut self, view: &str, seconds: f64) {
+        let remaining = self.disrupted.entry(view.to_string()).or_default();
+        *remaining = remaining.max(seconds);
+    }
+
+    pub fn is_disrupted(&self, view: &str) -> bool {
+        self.disrupted.contains_key(view)
+    }
+
+    /// Counts down every disruption, restoring feeds whose
+    /// time is up
+    pub fn tick(&mut self, delta: f64) {
+        self.disrupted.retain(|_, remaining| {
+            *remaining -= delta;
+            *remaining > 0.0
+        });
+    }
+
+    pub fn rng(&mut self) -> &mut SimpleRng {
+        &mut self.rng
+    }
+}
+
+/// Random noise drawn over a disrupted camera feed
+pub struct CameraStatic<'a>(pub &'a mut SimpleRng);
+
+impl CameraStatic<'_> {
+    const SYMBOLS: [&'static str; 5] = [" ", "·", "░", "▒", "▓"];
+
+    /// A burst of static that fades out over `duration_ms`,
+    /// letting the feed underneath show through again
+    pub fn burst(duration_ms: u32) -> Effect {
+        fx::effect_fn_buf(
+            SimpleRng::default(),
+            (duration_ms, Interpolation::QuadIn),
+            |rng, context, buf| {
+                for position in context.area.positions() {
+                    if rng.gen_f32() < context.alpha() {
+                        continue;
+                    }
+                    if let Some(cell) = buf.cell_mut(position) {
+                        noise(rng, cell);
+                    }
+                }
+            },
+        )
+    }
+}
+
+impl Widget for CameraStatic<'_> {
+    #[instrument(skip_all)]
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        for position in area.positions() {
+            if let Some(cell) = buf.cell_mut(position) {
+                noise(self.0, cell);
+            }
+        }
+    }
+}
+
+/// Replaces `cell` with a random speck of static
+fn noise(rng: &mut SimpleRng, cell: &mut ratatui::buffer::Cell) {
+    let noise = rng.r#gen();
+    let symbol = CameraStatic::SYMBOLS[noise as usize % CameraStatic::SYMBOLS.len()];
+    let shade = 64 + (noise >> 8) as u8 % 160;
+    cell.set_symbol(symbol)
+        .set_fg(Color::Rgb(shade, shade, shade))
+        .set_bg(Color::Reset);
+}
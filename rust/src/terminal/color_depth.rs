@@ -0,0 +1,161 @@
+use godot::prelude::*;
+use ratatui::{buffer::Buffer, style::Color};
+
+use crate::palette::{ANSI_COLORS, CUBE_LEVELS, Rgb, XTERM, indexed_to_rgb};
+
+/// How many colors the host terminal can show. Tile layers and
+/// effects use 24-bit colors, which are brought down to the
+/// nearest color the terminal has before drawing
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[godot(via = GString)]
+pub enum ColorDepth {
+    /// Guess from the `COLORTERM` and `TERM` environment variables
+    #[default]
+    Auto,
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Replaces `Auto` with what the environment says
+    /// the terminal supports
+    pub fn resolve(self) -> Self {
+        match self {
+            ColorDepth::Auto => Self::detect(
+                std::env::var("COLORTERM").ok().as_deref(),
+                std::env::var("TERM").ok().as_deref(),
+            ),
+            depth => depth,
+        }
+    }
+
+    fn detect(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        let colorterm = colorterm.unwrap_or_default().to_lowercase();
+        let term = term.unwrap_or_default().to_lowercase();
+        if colorterm == "truecolor"
+            || colorterm == "24bit"
+            || term.ends_with("-direct")
+            || term.contains("truecolor")
+        {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else if term.is_empty() && cfg!(windows) {
+            // The Windows console has no TERM but
+            // supports 24-bit colors
+            ColorDepth::TrueColor
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    /// Brings every color in `buf` down to this depth
+    pub fn quantize(self, buf: &mut Buffer) {
+        if matches!(self, ColorDepth::Auto | ColorDepth::TrueColor) {
+            return;
+        }
+        for cell in &mut buf.content {
+            cell.fg = self.color(cell.fg);
+            cell.bg = self.color(cell.bg);
+        }
+    }
+
+    fn color(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256((r, g, b))),
+            (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => nearest_16((r, g, b)),
+            (ColorDepth::Ansi16, Color::Indexed(index)) => nearest_16(indexed_rgb(index)),
+            (_, color) => color,
+        }
+    }
+}
+
+fn nearest_16(rgb: Rgb) -> Color {
+    ANSI_COLORS
+        .into_iter()
+        .zip(XTERM)
+        .min_by_key(|(_, candidate)| distance(rgb, *candidate))
+        .map(|(color, _)| color)
+        .unwrap()
+}
+
+/// The closest color in the 6x6x6 cube or the gray ramp
+fn nearest_256((r, g, b): Rgb) -> u8 {
+    let level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|index| CUBE_LEVELS[*index].abs_diff(value))
+            .unwrap() as u8
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+    let average = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23);
+    if distance((r, g, b), indexed_rgb(gray)) < distance((r, g, b), indexed_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn indexed_rgb(index: u8) -> Rgb {
+    indexed_to_rgb(index, &XTERM)
+}
+
+/// Squared distance, weighted by how sensitive eyes are to
+/// each channel
+fn distance(a: Rgb, b: Rgb) -> u32 {
+    let channel = |a: u8, b: u8, weight: u32| weight * (a.abs_diff(b) as u32).pow(2);
+    channel(a.0, b.0, 3) + channel(a.1, b.1, 4) + channel(a.2, b.2, 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{buffer::Buffer, layout::Rect, style::Color};
+
+    use crate::terminal::color_depth::ColorDepth;
+
+    #[test]
+    fn detects_from_environment() {
+        assert_eq!(
+            ColorDepth::detect(Some("truecolor"), Some("xterm-256color")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::detect(None, Some("xterm-direct")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::detect(None, Some("screen-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(ColorDepth::detect(None, Some("linux")), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn quantizes_to_256_colors() {
+        let depth = ColorDepth::Ansi256;
+        assert_eq!(depth.color(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(depth.color(Color::Rgb(0, 135, 255)), Color::Indexed(33));
+        assert_eq!(depth.color(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(depth.color(Color::Red), Color::Red);
+    }
+
+    #[test]
+    fn quantizes_to_16_colors() {
+        let depth = ColorDepth::Ansi16;
+        assert_eq!(depth.color(Color::Rgb(250, 10, 10)), Color::LightRed);
+        assert_eq!(depth.color(Color::Rgb(10, 10, 10)), Color::Black);
+        assert_eq!(depth.color(Color::Indexed(46)), Color::LightGreen);
+        assert_eq!(depth.color(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn quantizes_every_cell() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 1));
+        buf[(0, 0)].set_fg(Color::Rgb(255, 255, 255));
+        buf[(1, 0)].set_bg(Color::Rgb(0, 0, 0));
+        ColorDepth::Ansi16.quantize(&mut buf);
+        assert_eq!(buf[(0, 0)].fg, Color::White);
+        assert_eq!(buf[(1, 0)].bg, Color::Black);
+    }
+}
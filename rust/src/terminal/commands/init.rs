@@ -7,7 +7,7 @@ pub struct InitCommand {
     pub component: Component,
 }
 
-#[derive(Debug, ValueEnum, Clone, Copy)]
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
 pub enum Component {
     Vent,
     Camera,
@@ -1,5 +1,7 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
 
+use crate::terminal::commands::init::InitCommand;
+
 #[derive(Debug, Parser)]
 #[command(multicall = true)]
 pub struct MainCli {
@@ -9,10 +11,12 @@ pub struct MainCli {
 
 #[derive(Debug, Subcommand)]
 pub enum MainCommands {
-    /// Initializes the sewer security system
-    Init,
+    /// Initializes a component of the sewer security system
+    Init(InitCommand),
     /// Pauses the game
     Pause,
+    /// Shows whether each gate and vent is open
+    Status,
     /// Opens/Closes a gate
     Gate(GateArgs),
     /// Opens/Closes a vent
@@ -21,27 +25,17 @@ pub enum MainCommands {
     /// on said area
     Zoom {
         /// The area to zoom in on
-        #[arg(value_enum)]
-        area: Area,
+        area: String,
     },
 }
 
-#[derive(Clone, Copy, Debug, ValueEnum)]
-pub enum Area {
-    MainView,
-    Room1,
-    Room2,
-    Room3,
-    Room4,
-}
-
 #[derive(Debug, Args)]
 pub struct GateArgs {
     /// The gate which should be opened/closed
-    #[arg(value_parser = clap::value_parser!(u8).range(1..=4))]
-    pub gate: u8,
+    #[arg(index = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub gate: u32,
     /// The operation to perform on the specified gate
-    #[arg(value_enum)]
+    #[arg(index = 2, value_enum)]
     pub operation: GateOperation,
 }
 
@@ -0,0 +1,189 @@
+use clap::{Arg, Command};
+
+/// An in-progress Tab completion, kept around so repeated
+/// presses of Tab cycle through the candidates
+pub struct Completion {
+    candidates: Vec<String>,
+    /// The candidate currently inserted, `None` until the first
+    /// cycle of an ambiguous completion
+    index: Option<usize>,
+    /// Byte offset in the command of the word being completed
+    word_start: usize,
+}
+
+impl Completion {
+    /// Finds the completions for the word ending at the end of
+    /// `line`, walking the clap command tree for subcommands,
+    /// possible values and flags. `dynamic_values` supplies values
+    /// for arguments whose value parser can't list them
+    pub fn new(
+        mut command: Command,
+        line: &str,
+        dynamic_values: impl Fn(&Arg) -> Vec<String>,
+    ) -> Option<Self> {
+        command.build();
+        let word_start = line
+            .char_indices()
+            .rev()
+            .find(|(_, char)| char.is_whitespace())
+            .map_or(0, |(index, char)| index + char.len_utf8());
+        let word = &line[word_start..];
+
+        let mut command = &command;
+        let mut positionals = 0;
+        for previous_word in line[..word_start].split_whitespace() {
+            if previous_word.starts_with('-') {
+                continue;
+            }
+            match command.find_subcommand(previous_word) {
+                Some(subcommand) if positionals == 0 => command = subcommand,
+                _ => positionals += 1,
+            }
+        }
+
+        let mut candidates: Vec<String> = if word.starts_with('-') {
+            command
+                .get_arguments()
+                .filter(|arg| !arg.is_hide_set())
+                .filter_map(|arg| arg.get_long())
+                .map(|long| format!("--{long}"))
+                .collect()
+        } else if command.has_subcommands() && positionals == 0 {
+            command
+                .get_subcommands()
+                .filter(|subcommand| !subcommand.is_hide_set())
+                .map(|subcommand| subcommand.get_name().to_string())
+                .collect()
+        } else if let Some(arg) = command
+            .get_positionals()
+            .find(|arg| arg.get_index() == Some(positionals + 1))
+        {
+            let possible_values = arg.get_possible_values();
+            if possible_values.is_empty() {
+                dynamic_values(arg)
+            } else {
+                possible_values
+                    .iter()
+                    .filter(|value| !value.is_hide_set())
+                    .map(|value| value.get_name().to_string())
+                    .collect()
+            }
+        } else {
+            Vec::new()
+        };
+        candidates.retain(|candidate| candidate.starts_with(word));
+
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(Self {
+                candidates,
+                index: None,
+                word_start,
+            })
+        }
+    }
+
+    pub fn is_ambiguous(&self) -> bool {
+        self.candidates.len() > 1
+    }
+
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    pub fn word_start(&self) -> usize {
+        self.word_start
+    }
+
+    /// The text that should replace the word being completed.
+    /// Ambiguous completions start out as the longest common
+    /// prefix of the candidates
+    pub fn current(&self) -> String {
+        match self.index {
+            Some(index) => self.candidates[index].clone(),
+            None if self.is_ambiguous() => {
+                let first = &self.candidates[0];
+                let common_len = self.candidates[1..]
+                    .iter()
+                    .fold(first.len(), |len, candidate| {
+                        first
+                            .char_indices()
+                            .zip(candidate.chars())
+                            .take_while(|((index, a), b)| index < &len && a == b)
+                            .last()
+                            .map_or(0, |((index, a), _)| index + a.len_utf8())
+                    });
+                first[..common_len].to_string()
+            }
+            None => format!("{} ", self.candidates[0]),
+        }
+    }
+
+    pub fn cycle(&mut self, forward: bool) {
+        let len = self.candidates.len();
+        self.index = Some(match (self.index, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(index), true) => (index + 1) % len,
+            (Some(index), false) => (index + len - 1) % len,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::terminal::{TerminalState, completion::Completion, tests::sewer};
+
+    fn complete(line: &str) -> Option<Completion> {
+        let state = TerminalState::new(sewer());
+        Completion::new(state.main_cli(), line, |_| Vec::new())
+    }
+
+    #[test]
+    fn unique_prefix_completes_the_word() {
+        let completion = complete("zo").unwrap();
+        assert!(!completion.is_ambiguous());
+        assert_eq!(completion.word_start(), 0);
+        assert_eq!(completion.current(), "zoom ");
+    }
+
+    #[test]
+    fn ambiguous_prefix_lists_candidates() {
+        let completion = complete("").unwrap();
+        assert!(completion.is_ambiguous());
+        assert_eq!(
+            completion.candidates(),
+            ["init", "pause", "status", "zoom", "gate", "vent", "help"]
+        );
+        assert_eq!(completion.current(), "");
+        assert!(complete("x").is_none());
+    }
+
+    #[test]
+    fn repeated_tabs_cycle_through_rooms() {
+        let mut completion = complete("zoom ").unwrap();
+        assert_eq!(completion.word_start(), 5);
+        assert_eq!(completion.candidates(), ["main-view", "room1"]);
+        completion.cycle(true);
+        assert_eq!(completion.current(), "main-view");
+        completion.cycle(true);
+        assert_eq!(completion.current(), "room1");
+        completion.cycle(true);
+        assert_eq!(completion.current(), "main-view");
+        completion.cycle(false);
+        assert_eq!(completion.current(), "room1");
+
+        let mut completion = complete("zoom ").unwrap();
+        completion.cycle(false);
+        assert_eq!(completion.current(), "room1");
+        assert_eq!(complete("zoom r").unwrap().current(), "room1 ");
+    }
+
+    #[test]
+    fn gate_operations_follow_the_gate_id() {
+        let completion = complete("gate 1 ").unwrap();
+        assert_eq!(completion.candidates(), ["open", "close"]);
+        assert_eq!(complete("gate 1 c").unwrap().current(), "close ");
+    }
+}
@@ -0,0 +1,349 @@
+use std::fmt::Display;
+
+use clap::{FromArgMatches, Parser};
+
+use crate::terminal::{
+    TerminalState,
+    commands::{
+        init::{Component, InitCommand},
+        main::{GateOperation, MainCli, MainCommands},
+        pause::{PauseCli, PauseCommands},
+    },
+    doors::DoorKind,
+};
+
+/// Something a command asks the terminal to do. `dispatch` decides
+/// what a command does from the terminal's state alone, and the
+/// `Terminal` node carries the actions out, emitting any signals
+#[derive(Debug, PartialEq)]
+pub enum CommandAction {
+    /// Show text in the output pane. May contain ANSI escapes
+    Output(String),
+    /// Show an error in the output pane, in the theme's error color
+    Error(String),
+    /// The first component is being initialized, so the security
+    /// system as a whole is starting up
+    StartSystem,
+    Init(Component),
+    Pause(bool),
+    SetDoor {
+        kind: DoorKind,
+        id: u32,
+        opened: bool,
+    },
+    /// Switch the map to the view called `area`
+    Zoom {
+        area: String,
+    },
+}
+
+impl TerminalState {
+    /// Works out what a command line, already split into words,
+    /// should do
+    pub fn dispatch(&self, command: Vec<String>) -> Vec<CommandAction> {
+        if !self.paused {
+            self.main_command(command)
+        } else {
+            self.pause_command(command)
+        }
+    }
+
+    fn main_command(&self, command: Vec<String>) -> Vec<CommandAction> {
+        let cli = self
+            .main_cli()
+            .try_get_matches_from(command)
+            .and_then(|matches| MainCli::from_arg_matches(&matches));
+        let command = match cli {
+            Ok(command) => command.command,
+            Err(e) => return vec![clap_error(e)],
+        };
+        if self.power.is_out() && !matches!(command, MainCommands::Pause | MainCommands::Status) {
+            return vec![refusal("there is no power left to run that command")];
+        }
+        match command {
+            MainCommands::Init(InitCommand { component }) => {
+                if self.subsystems.is_online(component) {
+                    return vec![CommandAction::Output(format!(
+                        "The {} system is already online",
+                        component.name()
+                    ))];
+                }
+                let mut actions = Vec::new();
+                if !self.subsystems.any_online() {
+                    actions.push(CommandAction::StartSystem);
+                }
+                actions.push(CommandAction::Init(component));
+                actions
+            }
+            MainCommands::Pause => vec![CommandAction::Pause(true)],
+            MainCommands::Status => vec![CommandAction::Output(format!(
+                "{}power: {:.0}%",
+                self.doors.status_report(),
+                self.power.level().ceil()
+            ))],
+            MainCommands::Gate(gate_args) => vec![CommandAction::SetDoor {
+                kind: DoorKind::Gate,
+                id: gate_args.gate,
+                opened: matches!(gate_args.operation, GateOperation::Open),
+            }],
+            MainCommands::Vent(gate_args) => {
+                if let Err(refused) = self.require_online(Component::Vent) {
+                    return vec![refused];
+                }
+                vec![CommandAction::SetDoor {
+                    kind: DoorKind::Vent,
+                    id: gate_args.gate,
+                    opened: matches!(gate_args.operation, GateOperation::Open),
+                }]
+            }
+            MainCommands::Zoom { area } => {
+                if let Err(refused) = self.require_online(Component::Camera) {
+                    return vec![refused];
+                }
+                if self.views.current() == area {
+                    return Vec::new();
+                }
+                vec![CommandAction::Zoom { area }]
+            }
+        }
+    }
+
+    fn pause_command(&self, command: Vec<String>) -> Vec<CommandAction> {
+        match PauseCli::try_parse_from(command) {
+            Ok(command) => match command.command {
+                PauseCommands::UnPause => vec![CommandAction::Pause(false)],
+            },
+            Err(e) => vec![clap_error(e)],
+        }
+    }
+
+    /// Refuses a command if `component` hasn't been
+    /// initialized yet
+    fn require_online(&self, component: Component) -> Result<(), CommandAction> {
+        if self.subsystems.is_online(component) {
+            return Ok(());
+        }
+        let name = component.name();
+        Err(refusal(format!(
+            "the {name} system is offline, run `init {name}` first"
+        )))
+    }
+}
+
+/// Explains why a command was refused, worded like a clap error
+pub fn refusal(message: impl Display) -> CommandAction {
+    clap_error(clap::Error::raw(
+        clap::error::ErrorKind::InvalidValue,
+        format!("{message}\n"),
+    ))
+}
+
+/// Clap reports `--help` as an error too, but that's shown as
+/// regular output, keeping its styling
+fn clap_error(error: clap::Error) -> CommandAction {
+    if error.use_stderr() {
+        CommandAction::Error(error.render().to_string())
+    } else {
+        CommandAction::Output(error.render().ansi().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::terminal::{
+        TerminalState, commands::init::Component, dispatch::CommandAction, doors::DoorKind,
+        tests::sewer,
+    };
+
+    fn dispatch(state: &TerminalState, command: &str) -> Vec<CommandAction> {
+        state.dispatch(shlex::split(command).unwrap())
+    }
+
+    /// The text of the only action, which has to be output or an
+    /// error, without ANSI escapes
+    fn output(actions: Vec<CommandAction>) -> String {
+        match actions.as_slice() {
+            [CommandAction::Output(output) | CommandAction::Error(output)] => {
+                anstream::adapter::strip_str(output).to_string()
+            }
+            _ => panic!("expected a single output, got {actions:?}"),
+        }
+    }
+
+    #[test]
+    fn first_init_starts_the_system() {
+        let state = TerminalState::new(sewer());
+        assert_eq!(
+            dispatch(&state, "init camera"),
+            [
+                CommandAction::StartSystem,
+                CommandAction::Init(Component::Camera)
+            ]
+        );
+    }
+
+    #[test]
+    fn later_inits_only_init_the_component() {
+        let mut state = TerminalState::new(sewer());
+        state.subsystems.bring_online(Component::Camera);
+        assert_eq!(
+            dispatch(&state, "init vent"),
+            [CommandAction::Init(Component::Vent)]
+        );
+    }
+
+    #[test]
+    fn init_refuses_an_online_component() {
+        let mut state = TerminalState::new(sewer());
+        state.subsystems.bring_online(Component::Audio);
+        assert_eq!(
+            output(dispatch(&state, "init audio")),
+            "The audio system is already online"
+        );
+    }
+
+    #[test]
+    fn pause_and_unpause() {
+        let mut state = TerminalState::new(sewer());
+        assert_eq!(dispatch(&state, "pause"), [CommandAction::Pause(true)]);
+        state.paused = true;
+        assert_eq!(dispatch(&state, "un-pause"), [CommandAction::Pause(false)]);
+    }
+
+    #[test]
+    fn paused_terminal_only_accepts_pause_commands() {
+        let mut state = TerminalState::new(sewer());
+        state.paused = true;
+        assert!(output(dispatch(&state, "status")).contains("unrecognized subcommand 'status'"));
+    }
+
+    #[test]
+    fn status_lists_doors_and_power() {
+        let mut state = TerminalState::new(sewer());
+        state.doors = crate::terminal::doors::Doors::new(2, 1);
+        state.doors.set(DoorKind::Gate, 2, true);
+        assert_eq!(
+            output(dispatch(&state, "status")),
+            "gate 1: closed\ngate 2: open\nvent 1: closed\npower: 100%"
+        );
+    }
+
+    #[test]
+    fn gate_sets_the_door() {
+        let state = TerminalState::new(sewer());
+        assert_eq!(
+            dispatch(&state, "gate 3 open"),
+            [CommandAction::SetDoor {
+                kind: DoorKind::Gate,
+                id: 3,
+                opened: true
+            }]
+        );
+        assert_eq!(
+            dispatch(&state, "gate 1 close"),
+            [CommandAction::SetDoor {
+                kind: DoorKind::Gate,
+                id: 1,
+                opened: false
+            }]
+        );
+    }
+
+    #[test]
+    fn gate_rejects_doors_that_do_not_exist() {
+        let state = TerminalState::new(sewer());
+        let error = output(dispatch(&state, "gate 5 open"));
+        assert!(error.contains("invalid value '5'"), "{error}");
+        assert!(error.contains("[possible values: 1, 2, 3, 4]"), "{error}");
+    }
+
+    #[test]
+    fn vent_needs_the_vent_system() {
+        let mut state = TerminalState::new(sewer());
+        let error = output(dispatch(&state, "vent 2 close"));
+        assert!(
+            error.contains("the vent system is offline, run `init vent` first"),
+            "{error}"
+        );
+        state.subsystems.bring_online(Component::Vent);
+        assert_eq!(
+            dispatch(&state, "vent 2 close"),
+            [CommandAction::SetDoor {
+                kind: DoorKind::Vent,
+                id: 2,
+                opened: false
+            }]
+        );
+    }
+
+    #[test]
+    fn zoom_needs_the_camera_system() {
+        let state = TerminalState::new(sewer());
+        let error = output(dispatch(&state, "zoom room1"));
+        assert!(
+            error.contains("the camera system is offline, run `init camera` first"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn zoom_switches_to_other_views() {
+        let mut state = TerminalState::new(sewer());
+        state.subsystems.bring_online(Component::Camera);
+        assert_eq!(
+            dispatch(&state, "zoom room1"),
+            [CommandAction::Zoom {
+                area: "room1".to_string()
+            }]
+        );
+        assert_eq!(dispatch(&state, "zoom main-view"), []);
+    }
+
+    #[test]
+    fn zoom_rejects_unknown_views() {
+        let mut state = TerminalState::new(sewer());
+        state.subsystems.bring_online(Component::Camera);
+        let error = output(dispatch(&state, "zoom kitchen"));
+        assert!(
+            error.contains("[possible values: main-view, room1]"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn no_power_only_allows_pause_and_status() {
+        let mut state = TerminalState::new(sewer());
+        state.subsystems.bring_online(Component::Camera);
+        state.power.drain(1000.0, 1.0);
+        let error = output(dispatch(&state, "gate 1 open"));
+        assert!(
+            error.contains("there is no power left to run that command"),
+            "{error}"
+        );
+        assert_eq!(dispatch(&state, "pause"), [CommandAction::Pause(true)]);
+        assert!(output(dispatch(&state, "status")).ends_with("power: 0%"));
+    }
+
+    #[test]
+    fn unknown_commands_are_reported() {
+        let state = TerminalState::new(sewer());
+        let error = output(dispatch(&state, "reboot"));
+        assert!(
+            error.starts_with("error: unrecognized subcommand 'reboot'"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn errors_are_kept_apart_from_help() {
+        let state = TerminalState::new(sewer());
+        assert!(matches!(
+            dispatch(&state, "reboot").as_slice(),
+            [CommandAction::Error(_)]
+        ));
+        assert!(matches!(
+            dispatch(&state, "help").as_slice(),
+            [CommandAction::Output(_)]
+        ));
+    }
+}
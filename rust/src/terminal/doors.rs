@@ -0,0 +1,151 @@
+use std::fmt::Write;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+use tracing::instrument;
+
+use crate::terminal::theme::Theme;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoorKind {
+    Gate,
+    Vent,
+}
+
+impl DoorKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            DoorKind::Gate => "gate",
+            DoorKind::Vent => "vent",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Door {
+    opened: bool,
+    /// Closed since the player last set the door, rather than
+    /// left closed since the start, so it draws power
+    held: bool,
+}
+
+/// Whether each gate and vent in the sewer is open. Doors are
+/// numbered from 1, like the `gate` and `vent` commands
+pub struct Doors {
+    gates: Vec<Door>,
+    vents: Vec<Door>,
+}
+
+impl Doors {
+    pub fn new(gate_count: usize, vent_count: usize) -> Self {
+        Self {
+            gates: vec![Door::default(); gate_count],
+            vents: vec![Door::default(); vent_count],
+        }
+    }
+
+    pub fn is_open(&self, kind: DoorKind, id: usize) -> Option<bool> {
+        let door = self.doors(kind).get(id.checked_sub(1)?)?;
+        Some(door.opened)
+    }
+
+    pub fn set(&mut self, kind: DoorKind, id: usize, opened: bool) {
+        let doors = match kind {
+            DoorKind::Gate => &mut self.gates,
+            DoorKind::Vent => &mut self.vents,
+        };
+        if let Some(door) = id.checked_sub(1).and_then(|index| doors.get_mut(index)) {
+            *door = Door {
+                opened,
+                held: !opened,
+            };
+        }
+    }
+
+    pub fn count(&self, kind: DoorKind) -> usize {
+        self.doors(kind).len()
+    }
+
+    /// How many doors the player has closed and not reopened
+    pub fn held_count(&self) -> usize {
+        self.gates
+            .iter()
+            .chain(&self.vents)
+            .filter(|door| door.held)
+            .count()
+    }
+
+    /// A plain text listing of every door, for the `status` command
+    pub fn status_report(&self) -> String {
+        let mut report = String::new();
+        for kind in [DoorKind::Gate, DoorKind::Vent] {
+            for (index, door) in self.doors(kind).iter().enumerate() {
+                let _ = writeln!(
+                    report,
+                    "{} {}: {}",
+                    kind.name(),
+                    index + 1,
+                    if door.opened { "open" } else { "closed" }
+                );
+            }
+        }
+        report
+    }
+
+    fn doors(&self, kind: DoorKind) -> &[Door] {
+        match kind {
+            DoorKind::Gate => &self.gates,
+            DoorKind::Vent => &self.vents,
+        }
+    }
+}
+
+/// An indicator panel showing which doors are open, drawn
+/// in the colors of a theme
+pub struct DoorPanel<'a>(pub &'a Doors, pub &'a Theme);
+
+impl DoorPanel<'_> {
+    pub const WIDTH: u16 = 16;
+}
+
+impl Widget for DoorPanel<'_> {
+    #[instrument(skip_all)]
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let lines = [DoorKind::Gate, DoorKind::Vent]
+            .into_iter()
+            .flat_map(|kind| {
+                self.0
+                    .doors(kind)
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, door)| {
+                        let indicator = if door.opened {
+                            Span::raw("OPEN").fg(self.1.ok)
+                        } else {
+                            Span::raw("CLOSED").fg(self.1.error)
+                        };
+                        Line::from(vec![
+                            Span::raw(format!("{} {:<3}", kind.name(), index + 1)),
+                            indicator,
+                        ])
+                    })
+            })
+            .collect::<Vec<_>>();
+        Paragraph::new(lines)
+            .block(
+                Block::new()
+                    .borders(Borders::all())
+                    .border_style(self.1.border_style())
+                    .title("Doors"),
+            )
+            .render(area, buf);
+    }
+}
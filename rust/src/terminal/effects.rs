@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use ratatui::{buffer::Buffer, layout::Rect, style::Color};
+use tachyonfx::{
+    Effect, Interpolation, IntoEffect, Shader,
+    fx::{self, Glitch, IntoTemporaryEffect, RepeatMode},
+};
+
+/// A part of the terminal an effect can be confined to
+#[derive(Clone, Copy, Debug)]
+pub enum Region {
+    /// The whole terminal
+    Screen,
+    /// Everything above the output pane: the map, doors and power
+    Main,
+    /// Just the map
+    Map,
+    /// The command output pane
+    Output,
+    /// The command prompt
+    Prompt,
+}
+
+/// Effects that restart instead of stacking when they are
+/// triggered again while still running
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EffectKey {
+    #[default]
+    Jumpscare,
+    PowerFlicker,
+    CameraStatic,
+}
+
+/// Where each [`Region`] was laid out on a screen
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RegionAreas {
+    screen: Rect,
+    main: Rect,
+    map: Rect,
+    output: Rect,
+    prompt: Rect,
+}
+
+impl RegionAreas {
+    /// Moves `region` to where it was laid out
+    pub fn set(&mut self, region: Region, area: Rect) {
+        *self.area_mut(region) = area;
+    }
+
+    fn get(&self, region: Region) -> Rect {
+        match region {
+            Region::Screen => self.screen,
+            Region::Main => self.main,
+            Region::Map => self.map,
+            Region::Output => self.output,
+            Region::Prompt => self.prompt,
+        }
+    }
+
+    fn area_mut(&mut self, region: Region) -> &mut Rect {
+        match region {
+            Region::Screen => &mut self.screen,
+            Region::Main => &mut self.main,
+            Region::Map => &mut self.map,
+            Region::Output => &mut self.output,
+            Region::Prompt => &mut self.prompt,
+        }
+    }
+}
+
+struct PlayingEffect {
+    key: Option<EffectKey>,
+    region: Region,
+    effect: Effect,
+}
+
+/// Every effect currently playing on the terminal. Effects are
+/// scoped to a [`Region`], which follows the layout as the
+/// terminal is resized
+#[derive(Default)]
+pub struct TerminalEffects {
+    playing: Vec<PlayingEffect>,
+}
+
+impl TerminalEffects {
+    /// Plays `effect` in `region` alongside everything else
+    pub fn add(&mut self, region: Region, effect: Effect) {
+        self.playing.push(PlayingEffect {
+            key: None,
+            region,
+            effect,
+        });
+    }
+
+    /// Plays `effect` in `region`, cancelling the last effect
+    /// played under `key`
+    pub fn add_unique(&mut self, key: EffectKey, region: Region, effect: Effect) {
+        self.playing.retain(|playing| playing.key != Some(key));
+        self.playing.push(PlayingEffect {
+            key: Some(key),
+            region,
+            effect,
+        });
+    }
+
+    /// Advances every effect by `delta` seconds and draws it on
+    /// the primary screen, laid out as `areas`
+    pub fn process(&mut self, delta: f64, areas: &RegionAreas, buf: &mut Buffer) {
+        let delta = Duration::from_secs_f64(delta);
+        for playing in &mut self.playing {
+            playing
+                .effect
+                .process(delta, buf, areas.get(playing.region));
+        }
+        self.playing.retain(|playing| !playing.effect.done());
+    }
+
+    /// Draws every effect as it is now on another screen, laid
+    /// out as `areas`, without advancing it
+    pub fn draw_copy(&self, areas: &RegionAreas, buf: &mut Buffer) {
+        for playing in &self.playing {
+            playing
+                .effect
+                .clone()
+                .process(Duration::ZERO, buf, areas.get(playing.region));
+        }
+    }
+}
+
+/// A violent red glitch, for when something gets the player
+pub fn jumpscare_glitch() -> Effect {
+    fx::parallel(&[
+        Glitch::builder()
+            .cell_glitch_ratio(0.6)
+            .action_start_delay_ms(0..100)
+            .action_ms(50..200)
+            .build()
+            .into_effect()
+            .with_duration(Duration::from_millis(700)),
+        fx::fade_from(Color::Red, Color::Red, (700, Interpolation::ExpoOut)),
+    ])
+}
+
+/// The screen browning out a few times as power dips
+pub fn power_flicker() -> Effect {
+    fx::repeat(
+        fx::ping_pong(fx::fade_to(
+            Color::Black,
+            Color::Black,
+            (90, Interpolation::Linear),
+        )),
+        RepeatMode::Times(3),
+    )
+}
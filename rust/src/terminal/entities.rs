@@ -0,0 +1,82 @@
+use ratatui::style::Color;
+
+/// When a tracked entity is drawn on the terminal map
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    /// Drawn on every view
+    Always,
+    /// Only drawn when zoomed in on a room, like a security camera
+    Zoomed,
+}
+
+/// Something moving around the sewer that the terminal shows
+/// on its map
+pub struct TrackedEntity {
+    pub id: &'static str,
+    pub glyph: &'static str,
+    pub color: Color,
+    pub visibility: Visibility,
+    /// The entity's world position, `None` until Godot reports one
+    pub position: Option<(f32, f32)>,
+}
+
+impl TrackedEntity {
+    const fn new(
+        id: &'static str,
+        glyph: &'static str,
+        color: Color,
+        visibility: Visibility,
+    ) -> Self {
+        Self {
+            id,
+            glyph,
+            color,
+            visibility,
+            position: None,
+        }
+    }
+
+    pub fn is_visible(&self, zoomed: bool) -> bool {
+        match self.visibility {
+            Visibility::Always => true,
+            Visibility::Zoomed => zoomed,
+        }
+    }
+}
+
+/// Every entity the terminal tracks, in drawing order
+pub struct EntityRegistry {
+    entities: Vec<TrackedEntity>,
+}
+
+impl Default for EntityRegistry {
+    fn default() -> Self {
+        Self {
+            entities: vec![
+                TrackedEntity::new("rat", "r", Color::LightRed, Visibility::Zoomed),
+                TrackedEntity::new("possum", "p", Color::LightMagenta, Visibility::Zoomed),
+                TrackedEntity::new("raccoon", "R", Color::LightYellow, Visibility::Zoomed),
+                // The player is drawn last so it stays on top
+                TrackedEntity::new("player", "@", Color::Cyan, Visibility::Always),
+            ],
+        }
+    }
+}
+
+impl EntityRegistry {
+    /// Updates an entity's world position, returning `false` if
+    /// no entity is registered under `id`
+    pub fn set_position(&mut self, id: &str, x: f32, y: f32) -> bool {
+        match self.entities.iter_mut().find(|entity| entity.id == id) {
+            Some(entity) => {
+                entity.position = Some((x, y));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TrackedEntity> {
+        self.entities.iter()
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{Read, Write},
+};
+
+use godot::{classes::file_access::ModeFlags, prelude::*, tools::GFile};
+use tracing::instrument;
+
+/// A bounded ring of previously entered commands, navigable
+/// with Up/Down like a shell prompt
+pub struct CommandHistory {
+    entries: VecDeque<String>,
+    capacity: usize,
+    /// Index into `entries` of the command currently recalled
+    /// into the prompt, `None` while editing a fresh line
+    position: Option<usize>,
+    /// The line that was being typed before navigation started,
+    /// restored when navigating past the newest entry
+    draft: String,
+    /// Recalled entries that were changed before moving on, by
+    /// index, so they come back edited until a command is entered
+    edits: HashMap<usize, String>,
+}
+
+impl CommandHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            position: None,
+            draft: String::new(),
+            edits: HashMap::new(),
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.truncate();
+    }
+
+    /// Records a submitted command, skipping blank lines and
+    /// immediate repeats of the newest entry
+    pub fn push(&mut self, command: &str) {
+        self.reset_navigation();
+        let command = command.trim();
+        if command.is_empty() || self.entries.back().is_some_and(|last| last == command) {
+            return;
+        }
+        self.entries.push_back(command.to_string());
+        self.truncate();
+    }
+
+    /// Steps back to an older command. `current` is the line in
+    /// the prompt, which is kept as the draft when navigation starts
+    /// or as the edited version of the entry it was recalled from
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let position = match self.position {
+            None => self.entries.len().checked_sub(1)?,
+            Some(position) => position.saturating_sub(1),
+        };
+        self.keep(current);
+        self.position = Some(position);
+        Some(self.recalled(position))
+    }
+
+    /// Steps forward to a newer command, returning the draft once
+    /// the newest entry is passed
+    pub fn next(&mut self, current: &str) -> Option<&str> {
+        let position = self.position? + 1;
+        self.keep(current);
+        if position < self.entries.len() {
+            self.position = Some(position);
+            Some(self.recalled(position))
+        } else {
+            self.position = None;
+            Some(self.draft.as_str())
+        }
+    }
+
+    /// Remembers `current` as the draft or as an edit of the
+    /// recalled entry, whichever the prompt is showing
+    fn keep(&mut self, current: &str) {
+        match self.position {
+            None => self.draft = current.to_string(),
+            Some(position) if self.entries[position] == current => {
+                self.edits.remove(&position);
+            }
+            Some(position) => {
+                self.edits.insert(position, current.to_string());
+            }
+        }
+    }
+
+    fn recalled(&self, position: usize) -> &str {
+        self.edits.get(&position).unwrap_or(&self.entries[position])
+    }
+
+    pub fn reset_navigation(&mut self) {
+        self.position = None;
+        self.draft.clear();
+        self.edits.clear();
+    }
+
+    #[instrument(skip(self))]
+    pub fn load(&mut self, path: &GString) -> std::io::Result<()> {
+        let mut contents = String::new();
+        GFile::open(path, ModeFlags::READ)?.read_to_string(&mut contents)?;
+        self.entries.clear();
+        self.reset_navigation();
+        self.entries.extend(
+            contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string),
+        );
+        self.truncate();
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub fn save(&self, path: &GString) -> std::io::Result<()> {
+        let mut file = GFile::open(path, ModeFlags::WRITE)?;
+        for entry in &self.entries {
+            writeln!(file, "{entry}")?;
+        }
+        file.flush()
+    }
+
+    fn truncate(&mut self) {
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::terminal::history::CommandHistory;
+
+    fn history() -> CommandHistory {
+        let mut history = CommandHistory::new(10);
+        history.push("gate 1 open");
+        history.push("zoom room1");
+        history
+    }
+
+    #[test]
+    fn restores_the_draft() {
+        let mut history = history();
+        assert_eq!(history.previous("ga"), Some("zoom room1"));
+        assert_eq!(history.previous("zoom room1"), Some("gate 1 open"));
+        assert_eq!(history.previous("gate 1 open"), Some("gate 1 open"));
+        assert_eq!(history.next("gate 1 open"), Some("zoom room1"));
+        assert_eq!(history.next("zoom room1"), Some("ga"));
+        assert_eq!(history.next("ga"), None);
+    }
+
+    #[test]
+    fn keeps_edits_to_recalled_entries() {
+        let mut history = history();
+        history.previous("");
+        assert_eq!(history.previous("zoom room2"), Some("gate 1 open"));
+        assert_eq!(history.next("gate 2 open"), Some("zoom room2"));
+        assert_eq!(history.previous("zoom room2"), Some("gate 2 open"));
+        assert_eq!(history.next("gate 1 open"), Some("zoom room2"));
+        assert_eq!(history.previous("zoom room2"), Some("gate 1 open"));
+    }
+
+    #[test]
+    fn forgets_edits_once_a_command_is_entered() {
+        let mut history = history();
+        history.previous("");
+        history.previous("zoom room2");
+        history.push("zoom room2");
+        assert_eq!(history.previous(""), Some("zoom room2"));
+        assert_eq!(history.previous("zoom room2"), Some("zoom room1"));
+    }
+}
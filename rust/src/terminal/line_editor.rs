@@ -0,0 +1,500 @@
+use std::{collections::VecDeque, ops::Range};
+
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::Stylize,
+    text::{Line, Span},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::terminal::cursor_x::CursorX;
+
+const KILL_RING_CAPACITY: usize = 16;
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+const ELLIPSIS: &str = "…";
+
+#[derive(Default, Clone)]
+enum LastEdit {
+    #[default]
+    Other,
+    /// The last edit killed text, so the next kill extends the
+    /// newest kill ring entry instead of pushing a new one
+    Kill,
+    /// The last edit yanked text into this byte range, so
+    /// yank-pop can swap it for an older kill
+    Yank(Range<usize>, usize),
+}
+
+/// A readline-style single line editor for the terminal prompt
+#[derive(Default)]
+pub struct LineEditor {
+    line: String,
+    cursor_x: CursorX,
+    kill_ring: VecDeque<String>,
+    last_edit: LastEdit,
+    /// How many columns of the line are scrolled off the left
+    /// edge of the prompt
+    scroll: usize,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> &str {
+        &self.line
+    }
+
+    pub fn cursor_x(&self) -> CursorX {
+        self.cursor_x
+    }
+
+    /// The text left of the cursor
+    pub fn before_cursor(&self) -> &str {
+        &self.line[..self.cursor_x.byte()]
+    }
+
+    /// Replaces the whole line, leaving the cursor at the end
+    pub fn set(&mut self, text: &str) {
+        self.line = text.to_string();
+        self.move_to(self.line.len());
+    }
+
+    /// Takes the line out of the editor, leaving it empty
+    pub fn take(&mut self) -> String {
+        self.last_edit = LastEdit::Other;
+        self.cursor_x = CursorX::default();
+        self.scroll = 0;
+        std::mem::take(&mut self.line)
+    }
+
+    /// Replaces `range` with `text`, leaving the cursor after it
+    pub fn replace_range(&mut self, range: Range<usize>, text: &str) {
+        let start = range.start;
+        self.line.replace_range(range, text);
+        self.move_to(start + text.len());
+    }
+
+    /// Scrolls the line so the cursor stays inside a prompt `width`
+    /// columns wide
+    pub fn scroll_to_fit(&mut self, width: usize) {
+        self.scroll = self.fitted_scroll(width);
+    }
+
+    /// The part of the line visible in a prompt `width` columns
+    /// wide, with ellipses on clipped sides, and the cursor's column
+    /// within it. Starts from where `scroll_to_fit` left the line,
+    /// scrolling further if the cursor would be hidden
+    pub fn viewport(&self, width: usize) -> (Line<'static>, usize) {
+        if width == 0 {
+            return (Line::default(), 0);
+        }
+        let line_width = self.line.width();
+        let cursor = self.cursor_x.column();
+        let scroll = self.fitted_scroll(width);
+        let left_clipped = scroll > 0;
+        let right_clipped = line_width > scroll + width;
+        let start = scroll + left_clipped as usize;
+        let end = scroll + width - right_clipped as usize;
+        let mut visible = String::new();
+        let mut column = 0;
+        for cluster in clusters(&self.line) {
+            let cluster_start = column;
+            column += cluster.width();
+            if column > end {
+                break;
+            }
+            if cluster_start >= start {
+                visible.push_str(cluster);
+            } else if column > start {
+                // A wide character cut in half by the left edge
+                visible.extend(std::iter::repeat_n(' ', column - start));
+            }
+        }
+
+        let mut spans = Vec::with_capacity(3);
+        if left_clipped {
+            spans.push(Span::raw(ELLIPSIS).dim());
+        }
+        if right_clipped {
+            let padding = (end - start).saturating_sub(visible.width());
+            visible.extend(std::iter::repeat_n(' ', padding));
+            spans.push(Span::raw(visible));
+            spans.push(Span::raw(ELLIPSIS).dim());
+        } else {
+            spans.push(Span::raw(visible));
+        }
+        (Line::from(spans), cursor - scroll)
+    }
+
+    fn fitted_scroll(&self, width: usize) -> usize {
+        let line_width = self.line.width();
+        let cursor = self.cursor_x.column();
+        if line_width < width || width < 3 {
+            return 0;
+        }
+        let mut scroll = self.scroll;
+        if cursor <= scroll {
+            scroll = cursor.saturating_sub(1);
+        }
+        if cursor + 2 > scroll + width {
+            scroll = cursor + 2 - width;
+        }
+        scroll.min(line_width + 1 - width)
+    }
+
+    /// Applies a key press to the line, returning whether the key
+    /// was bound to an editing action
+    pub fn key(&mut self, key: KeyEvent) -> bool {
+        if key.kind != KeyEventKind::Press {
+            return false;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let last_edit = std::mem::take(&mut self.last_edit);
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.move_to(0),
+            KeyCode::Char('e') if ctrl => self.move_to(self.line.len()),
+            KeyCode::Char('b') if ctrl => self.move_to(self.previous_boundary()),
+            KeyCode::Char('f') if ctrl => self.move_to(self.next_boundary()),
+            KeyCode::Char('b') if alt => self.move_to(self.previous_word()),
+            KeyCode::Char('f') if alt => self.move_to(self.next_word()),
+            KeyCode::Char('d') if ctrl => self.delete(self.cursor_x.byte()..self.next_boundary()),
+            KeyCode::Char('d') if alt => {
+                self.kill(self.cursor_x.byte()..self.next_word(), last_edit, false)
+            }
+            KeyCode::Char('w') if ctrl => self.kill(
+                self.previous_whitespace()..self.cursor_x.byte(),
+                last_edit,
+                true,
+            ),
+            KeyCode::Char('u') if ctrl => self.kill(0..self.cursor_x.byte(), last_edit, true),
+            KeyCode::Char('k') if ctrl => {
+                self.kill(self.cursor_x.byte()..self.line.len(), last_edit, false)
+            }
+            KeyCode::Char('y') if ctrl => self.yank(),
+            KeyCode::Char('y') if alt => self.yank_pop(last_edit),
+            KeyCode::Char(_) if ctrl || alt => {
+                self.last_edit = last_edit;
+                return false;
+            }
+            KeyCode::Char(char) => {
+                let byte = self.cursor_x.byte();
+                self.line.insert(byte, char);
+                self.move_to(byte + char.len_utf8());
+            }
+            KeyCode::Left if ctrl => self.move_to(self.previous_word()),
+            KeyCode::Right if ctrl => self.move_to(self.next_word()),
+            KeyCode::Left => self.move_to(self.previous_boundary()),
+            KeyCode::Right => self.move_to(self.next_boundary()),
+            KeyCode::Home => self.move_to(0),
+            KeyCode::End => self.move_to(self.line.len()),
+            KeyCode::Backspace if ctrl => self.kill(0..self.line.len(), last_edit, true),
+            KeyCode::Backspace => self.delete(self.previous_boundary()..self.cursor_x.byte()),
+            KeyCode::Delete => self.delete(self.cursor_x.byte()..self.next_boundary()),
+            _ => {
+                self.last_edit = last_edit;
+                return false;
+            }
+        }
+        true
+    }
+
+    fn move_to(&mut self, byte: usize) {
+        self.cursor_x = CursorX::at(&self.line, byte);
+    }
+
+    fn delete(&mut self, range: Range<usize>) {
+        let start = range.start;
+        self.line.replace_range(range, "");
+        self.move_to(start);
+    }
+
+    fn kill(&mut self, range: Range<usize>, last_edit: LastEdit, backward: bool) {
+        if range.is_empty() {
+            return;
+        }
+        let killed = &self.line[range.clone()];
+        match (last_edit, self.kill_ring.back_mut()) {
+            (LastEdit::Kill, Some(newest)) if backward => newest.insert_str(0, killed),
+            (LastEdit::Kill, Some(newest)) => newest.push_str(killed),
+            _ => {
+                self.kill_ring.push_back(killed.to_string());
+                if self.kill_ring.len() > KILL_RING_CAPACITY {
+                    self.kill_ring.pop_front();
+                }
+            }
+        }
+        self.delete(range);
+        self.last_edit = LastEdit::Kill;
+    }
+
+    fn yank(&mut self) {
+        let Some(newest) = self.kill_ring.back() else {
+            return;
+        };
+        let start = self.cursor_x.byte();
+        self.line.insert_str(start, newest);
+        let end = start + newest.len();
+        self.move_to(end);
+        self.last_edit = LastEdit::Yank(start..end, self.kill_ring.len() - 1);
+    }
+
+    fn yank_pop(&mut self, last_edit: LastEdit) {
+        let LastEdit::Yank(range, ring_index) = last_edit else {
+            return;
+        };
+        let ring_index = ring_index
+            .checked_sub(1)
+            .unwrap_or(self.kill_ring.len() - 1);
+        let older = &self.kill_ring[ring_index];
+        let start = range.start;
+        self.line.replace_range(range, older);
+        let end = start + older.len();
+        self.move_to(end);
+        self.last_edit = LastEdit::Yank(start..end, ring_index);
+    }
+
+    /// The start of the character cluster before the cursor, treating
+    /// combining marks and zero width joiner sequences as one unit
+    fn previous_boundary(&self) -> usize {
+        let mut chars = self.before_cursor().char_indices().rev().peekable();
+        while let Some((index, char)) = chars.next() {
+            let joined = chars
+                .peek()
+                .is_some_and(|(_, previous)| *previous == ZERO_WIDTH_JOINER);
+            if !is_extending(char) && !joined {
+                return index;
+            }
+        }
+        0
+    }
+
+    /// The end of the character cluster after the cursor
+    fn next_boundary(&self) -> usize {
+        let byte = self.cursor_x.byte();
+        byte + clusters(&self.line[byte..]).next().map_or(0, str::len)
+    }
+
+    fn previous_word(&self) -> usize {
+        let before = self.before_cursor();
+        let end = before.trim_end_matches(|char: char| !char.is_alphanumeric());
+        end.trim_end_matches(char::is_alphanumeric).len()
+    }
+
+    fn next_word(&self) -> usize {
+        let byte = self.cursor_x.byte();
+        let after = &self.line[byte..];
+        let start = after.trim_start_matches(|char: char| !char.is_alphanumeric());
+        let end = start.trim_start_matches(char::is_alphanumeric);
+        self.line.len() - end.len()
+    }
+
+    fn previous_whitespace(&self) -> usize {
+        self.before_cursor()
+            .trim_end()
+            .trim_end_matches(|char: char| !char.is_whitespace())
+            .len()
+    }
+}
+
+/// Splits `text` into character clusters, keeping combining marks
+/// and zero width joiner sequences with the character they follow
+fn clusters(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let mut chars = rest.char_indices();
+        let (_, mut previous) = chars.next()?;
+        let end = chars
+            .find(|&(_, char)| {
+                let boundary = !is_extending(char) && previous != ZERO_WIDTH_JOINER;
+                previous = char;
+                boundary
+            })
+            .map_or(rest.len(), |(index, _)| index);
+        let (cluster, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some(cluster)
+    })
+}
+
+fn is_extending(char: char) -> bool {
+    char.width() == Some(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+        text::Line,
+    };
+
+    use crate::terminal::line_editor::LineEditor;
+
+    fn press(editor: &mut LineEditor, code: KeyCode, modifiers: KeyModifiers) {
+        assert!(editor.key(KeyEvent::new(code, modifiers)));
+    }
+
+    fn ctrl(editor: &mut LineEditor, char: char) {
+        press(editor, KeyCode::Char(char), KeyModifiers::CONTROL);
+    }
+
+    fn alt(editor: &mut LineEditor, char: char) {
+        press(editor, KeyCode::Char(char), KeyModifiers::ALT);
+    }
+
+    fn type_text(editor: &mut LineEditor, text: &str) {
+        for char in text.chars() {
+            press(editor, KeyCode::Char(char), KeyModifiers::NONE);
+        }
+    }
+
+    fn editing(text: &str) -> LineEditor {
+        let mut editor = LineEditor::default();
+        editor.set(text);
+        editor
+    }
+
+    #[test]
+    fn kill_keys_cut_around_the_cursor() {
+        let mut editor = editing("zoom room1  ");
+        ctrl(&mut editor, 'w');
+        assert_eq!(editor.text(), "zoom ");
+
+        let mut editor = editing("gate 1 open");
+        for _ in 0.." open".len() {
+            press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        }
+        ctrl(&mut editor, 'u');
+        assert_eq!(editor.text(), " open");
+        assert_eq!(editor.cursor_x().column(), 0);
+
+        let mut editor = editing("gate 1 open");
+        ctrl(&mut editor, 'a');
+        alt(&mut editor, 'f');
+        ctrl(&mut editor, 'k');
+        assert_eq!(editor.text(), "gate");
+        assert_eq!(editor.cursor_x().column(), 4);
+    }
+
+    #[test]
+    fn consecutive_kills_join_in_reading_order() {
+        let mut editor = editing("one two three");
+        ctrl(&mut editor, 'w');
+        ctrl(&mut editor, 'w');
+        assert_eq!(editor.text(), "one ");
+        ctrl(&mut editor, 'y');
+        assert_eq!(editor.text(), "one two three");
+
+        let mut editor = editing("one two three");
+        ctrl(&mut editor, 'a');
+        alt(&mut editor, 'd');
+        alt(&mut editor, 'd');
+        assert_eq!(editor.text(), " three");
+        ctrl(&mut editor, 'e');
+        ctrl(&mut editor, 'y');
+        assert_eq!(editor.text(), " threeone two");
+    }
+
+    #[test]
+    fn yank_pop_wraps_around_the_kill_ring() {
+        let mut editor = LineEditor::default();
+        for kill in ["one", "two", "three"] {
+            type_text(&mut editor, kill);
+            ctrl(&mut editor, 'u');
+        }
+        type_text(&mut editor, "> ");
+        ctrl(&mut editor, 'y');
+        assert_eq!(editor.text(), "> three");
+        for yanked in ["> two", "> one", "> three"] {
+            alt(&mut editor, 'y');
+            assert_eq!(editor.text(), yanked);
+            assert_eq!(editor.cursor_x().column(), yanked.len());
+        }
+        type_text(&mut editor, "!");
+        alt(&mut editor, 'y');
+        assert_eq!(editor.text(), "> three!");
+    }
+
+    #[test]
+    fn word_motions_stop_at_punctuation() {
+        let mut editor = editing("zoom main-view");
+        for column in [10, 5, 0] {
+            alt(&mut editor, 'b');
+            assert_eq!(editor.cursor_x().column(), column);
+        }
+        for column in [4, 9] {
+            alt(&mut editor, 'f');
+            assert_eq!(editor.cursor_x().column(), column);
+        }
+        alt(&mut editor, 'd');
+        assert_eq!(editor.text(), "zoom main");
+    }
+
+    #[test]
+    fn combining_marks_move_with_their_character() {
+        let mut editor = editing("cafe\u{301}!");
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(editor.cursor_x().column(), 3);
+        press(&mut editor, KeyCode::Delete, KeyModifiers::NONE);
+        assert_eq!(editor.text(), "caf!");
+
+        let mut editor = editing("cafe\u{301}");
+        press(&mut editor, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(editor.text(), "caf");
+    }
+
+    #[test]
+    fn viewport_marks_clipped_sides_with_ellipses() {
+        let mut editor = editing("gate 1 open && gate 2 open");
+        editor.scroll_to_fit(12);
+        let (line, column) = editor.viewport(12);
+        assert_eq!(line.to_string(), "…ate 2 open");
+        assert_eq!(column, 11);
+        for _ in 0..12 {
+            press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        }
+        let (line, column) = editor.viewport(12);
+        assert_eq!(line.to_string(), "… gate 2 op…");
+        assert_eq!(column, 1);
+    }
+
+    #[test]
+    fn viewport_pads_wide_characters_cut_by_the_left_edge() {
+        let editor = editing("ab漢字cd");
+        let (line, column) = editor.viewport(5);
+        assert_eq!(line.to_string(), "… cd");
+        assert_eq!(column, 4);
+    }
+
+    #[test]
+    fn cursor_stays_in_view_moving_left() {
+        let mut editor = editing("gate 1 open && gate 2 open");
+        while editor.cursor_x().column() > 0 {
+            editor.scroll_to_fit(12);
+            let (line, column) = editor.viewport(12);
+            assert!(line.width() <= 12);
+            assert!((1..12).contains(&column) || editor.scroll == 0);
+            press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        }
+        editor.scroll_to_fit(12);
+        assert_eq!(editor.viewport(12).0.to_string(), "gate 1 open…");
+    }
+
+    #[test]
+    fn zero_width_viewport_is_empty() {
+        let editor = editing("zoom");
+        assert_eq!(editor.viewport(0), (Line::default(), 0));
+    }
+
+    #[test]
+    fn zero_width_joiner_sequences_move_as_one() {
+        let mut editor = editing("a\u{1F469}\u{200D}\u{1F4BB}b");
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(editor.cursor_x().column(), 1);
+        ctrl(&mut editor, 'f');
+        assert_eq!(editor.before_cursor(), "a\u{1F469}\u{200D}\u{1F4BB}");
+        press(&mut editor, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(editor.text(), "ab");
+        assert_eq!(editor.cursor_x().column(), 1);
+    }
+}
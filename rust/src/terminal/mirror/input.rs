@@ -0,0 +1,256 @@
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+/// Something a mirror client sent over its socket
+#[derive(Debug, PartialEq)]
+pub enum MirrorInput {
+    Event(Event),
+    /// The client's terminal is `columns` wide and `rows` tall
+    Resize {
+        columns: u16,
+        rows: u16,
+    },
+}
+
+/// How long, in seconds, an escape sequence may take to arrive in
+/// full before what came so far is read as the keys themselves
+const ESCAPE_TIMEOUT: f64 = 0.1;
+
+/// Turns the raw bytes a terminal in raw mode sends into key
+/// events. Escape sequences split across two reads are kept
+/// until the rest arrives
+#[derive(Default)]
+pub struct InputParser {
+    pending: Vec<u8>,
+    /// How long `pending` has waited for more bytes, in seconds
+    waited: f64,
+}
+
+impl InputParser {
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<MirrorInput> {
+        if !bytes.is_empty() {
+            self.waited = 0.0;
+        }
+        self.pending.extend_from_slice(bytes);
+        let mut inputs = Vec::new();
+        let mut rest = &self.pending[..];
+        while !rest.is_empty() {
+            match parse(rest) {
+                Parsed::Input(input, length) => {
+                    inputs.extend(input);
+                    rest = &rest[length..];
+                }
+                Parsed::Incomplete => break,
+            }
+        }
+        let consumed = self.pending.len() - rest.len();
+        self.pending.drain(..consumed);
+        inputs
+    }
+
+    /// Gives up on an unfinished sequence once it has waited
+    /// `ESCAPE_TIMEOUT` seconds. A lone escape is then the Escape
+    /// key itself
+    pub fn tick(&mut self, delta: f64) -> Vec<MirrorInput> {
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+        self.waited += delta;
+        if self.waited < ESCAPE_TIMEOUT {
+            return Vec::new();
+        }
+        self.waited = 0.0;
+        let mut inputs = Vec::new();
+        if self.pending.remove(0) == 0x1b {
+            inputs.extend(key(KeyCode::Esc, KeyModifiers::NONE));
+        }
+        inputs.extend(self.feed(&[]));
+        inputs
+    }
+}
+
+enum Parsed {
+    /// The input at the start of the bytes, if it meant anything,
+    /// and how many bytes it took up
+    Input(Option<MirrorInput>, usize),
+    Incomplete,
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> Option<MirrorInput> {
+    Some(MirrorInput::Event(Event::Key(KeyEvent::new(
+        code, modifiers,
+    ))))
+}
+
+fn parse(bytes: &[u8]) -> Parsed {
+    match bytes[0] {
+        0x1b => parse_escape(bytes),
+        b'\r' | b'\n' => Parsed::Input(key(KeyCode::Enter, KeyModifiers::NONE), 1),
+        b'\t' => Parsed::Input(key(KeyCode::Tab, KeyModifiers::NONE), 1),
+        0x7f | 0x08 => Parsed::Input(key(KeyCode::Backspace, KeyModifiers::NONE), 1),
+        control @ 0x01..=0x1a => Parsed::Input(
+            key(
+                KeyCode::Char((control - 1 + b'a') as char),
+                KeyModifiers::CONTROL,
+            ),
+            1,
+        ),
+        0x00 | 0x1c..=0x1f => Parsed::Input(None, 1),
+        first => {
+            let length = match first {
+                0x20..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => return Parsed::Input(None, 1),
+            };
+            let Some(char_bytes) = bytes.get(..length) else {
+                return Parsed::Incomplete;
+            };
+            match std::str::from_utf8(char_bytes)
+                .ok()
+                .and_then(|char| char.chars().next())
+            {
+                Some(char) => Parsed::Input(key(KeyCode::Char(char), KeyModifiers::NONE), length),
+                None => Parsed::Input(None, 1),
+            }
+        }
+    }
+}
+
+fn parse_escape(bytes: &[u8]) -> Parsed {
+    match bytes.get(1) {
+        // Either the Escape key or the start of a sequence,
+        // which `InputParser::tick` tells apart
+        None => Parsed::Incomplete,
+        Some(b'[') => parse_csi(bytes),
+        Some(b'O') => match bytes.get(2) {
+            None => Parsed::Incomplete,
+            Some(final_byte) => Parsed::Input(
+                cursor_key(*final_byte).and_then(|code| key(code, KeyModifiers::NONE)),
+                3,
+            ),
+        },
+        Some(0x1b) => Parsed::Input(key(KeyCode::Esc, KeyModifiers::NONE), 1),
+        Some(_) => match parse(&bytes[1..]) {
+            Parsed::Input(Some(MirrorInput::Event(Event::Key(mut key))), length) => {
+                key.modifiers |= KeyModifiers::ALT;
+                Parsed::Input(Some(MirrorInput::Event(Event::Key(key))), length + 1)
+            }
+            Parsed::Input(input, length) => Parsed::Input(input, length + 1),
+            Parsed::Incomplete => Parsed::Incomplete,
+        },
+    }
+}
+
+/// Parses `ESC [ <parameters> <final byte>`
+fn parse_csi(bytes: &[u8]) -> Parsed {
+    let Some(end) = bytes[2..]
+        .iter()
+        .position(|byte| (0x40..=0x7e).contains(byte))
+    else {
+        return Parsed::Incomplete;
+    };
+    let length = end + 3;
+    let final_byte = bytes[length - 1];
+    let parameters = std::str::from_utf8(&bytes[2..length - 1])
+        .unwrap_or_default()
+        .split(';')
+        .map(|parameter| parameter.parse::<u16>().unwrap_or(1))
+        .collect::<Vec<_>>();
+    let modifiers = parameters
+        .get(1)
+        .map_or(KeyModifiers::NONE, |modifier| modifiers(*modifier));
+    let input = match (final_byte, parameters.as_slice()) {
+        // The reply to the size query sent by the mirror
+        (b't', [8, rows, columns]) => Some(MirrorInput::Resize {
+            columns: *columns,
+            rows: *rows,
+        }),
+        (b'Z', _) => key(KeyCode::BackTab, KeyModifiers::SHIFT),
+        (b'~', [code, ..]) => {
+            let code = match code {
+                1 | 7 => Some(KeyCode::Home),
+                2 => Some(KeyCode::Insert),
+                3 => Some(KeyCode::Delete),
+                4 | 8 => Some(KeyCode::End),
+                5 => Some(KeyCode::PageUp),
+                6 => Some(KeyCode::PageDown),
+                _ => None,
+            };
+            code.and_then(|code| key(code, modifiers))
+        }
+        (final_byte, _) => cursor_key(final_byte).and_then(|code| key(code, modifiers)),
+    };
+    Parsed::Input(input, length)
+}
+
+fn cursor_key(final_byte: u8) -> Option<KeyCode> {
+    match final_byte {
+        b'A' => Some(KeyCode::Up),
+        b'B' => Some(KeyCode::Down),
+        b'C' => Some(KeyCode::Right),
+        b'D' => Some(KeyCode::Left),
+        b'H' => Some(KeyCode::Home),
+        b'F' => Some(KeyCode::End),
+        _ => None,
+    }
+}
+
+/// Decodes the `1 + bitmask` modifier parameter xterm
+/// sends with modified keys
+fn modifiers(parameter: u16) -> KeyModifiers {
+    let mask = parameter.saturating_sub(1);
+    let mut modifiers = KeyModifiers::NONE;
+    if mask & 1 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if mask & 2 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if mask & 4 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    modifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::terminal::mirror::input::{ESCAPE_TIMEOUT, InputParser, MirrorInput};
+
+    fn key(code: KeyCode) -> MirrorInput {
+        MirrorInput::Event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    #[test]
+    fn joins_sequences_split_across_reads() {
+        let mut parser = InputParser::default();
+        assert_eq!(parser.feed(b"\x1b"), []);
+        assert_eq!(parser.tick(ESCAPE_TIMEOUT / 2.0), []);
+        assert_eq!(parser.feed(b"[A"), [key(KeyCode::Up)]);
+        assert_eq!(parser.feed(b"\x1b["), []);
+        assert_eq!(parser.feed(b"B"), [key(KeyCode::Down)]);
+    }
+
+    #[test]
+    fn lone_escape_is_the_escape_key_after_a_pause() {
+        let mut parser = InputParser::default();
+        assert_eq!(parser.feed(b"\x1b"), []);
+        assert_eq!(parser.tick(ESCAPE_TIMEOUT), [key(KeyCode::Esc)]);
+        assert_eq!(parser.tick(ESCAPE_TIMEOUT), []);
+        assert_eq!(parser.feed(b"a"), [key(KeyCode::Char('a'))]);
+    }
+
+    #[test]
+    fn reads_size_reports() {
+        let mut parser = InputParser::default();
+        assert_eq!(
+            parser.feed(b"\x1b[8;24;80t"),
+            [MirrorInput::Resize {
+                columns: 80,
+                rows: 24
+            }]
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    io::{self, Read, Write},
+    net::Shutdown,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::Duration,
+};
+
+use ratatui::{
+    Frame, TerminalOptions, Viewport,
+    crossterm::{
+        ExecutableCommand,
+        event::Event,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::Rect,
+    prelude::CrosstermBackend,
+};
+
+use crate::terminal::mirror::input::{InputParser, MirrorInput};
+
+mod input;
+
+/// Asks the client's terminal for its size in characters. The
+/// reply comes back as `ESC [ 8 ; rows ; columns t`
+const SIZE_QUERY: &[u8] = b"\x1b[18t";
+/// How often clients are asked for their size, in seconds, so
+/// resized windows are noticed
+const SIZE_QUERY_INTERVAL: f64 = 1.0;
+/// Used until a client answers the size query
+const DEFAULT_SIZE: Rect = Rect::new(0, 0, 80, 24);
+
+/// Shares the terminal with other terminal emulators through a
+/// Unix socket. Any terminal in raw mode can attach, for example
+/// with `socat -,raw,echo=0 UNIX-CONNECT:<socket>`. Each client is
+/// drawn at its own size, and its keyboard input is sent back
+/// to the terminal
+pub struct MirrorServer {
+    listener: UnixListener,
+    path: PathBuf,
+    clients: Vec<MirrorClient>,
+    /// The emulator window opened with Ctrl+Shift+P, if any
+    emulator: Option<Child>,
+}
+
+struct MirrorClient {
+    terminal: ratatui::Terminal<CrosstermBackend<UnixStream>>,
+    /// Another handle to the socket, shut down when the client
+    /// is dropped so its reader thread stops
+    socket: UnixStream,
+    /// Bytes read from the socket by the client's reader thread
+    input: Receiver<Vec<u8>>,
+    parser: InputParser,
+    since_size_query: f64,
+}
+
+impl MirrorServer {
+    /// Listens on `path`, replacing any socket left behind by a
+    /// previous run. Fails if another game is still listening there,
+    /// or if something other than a socket is in the way
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) if !metadata.file_type().is_socket() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", path.display()),
+                ));
+            }
+            Ok(_) => {
+                if UnixStream::connect(path).is_ok() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("another game is mirroring to {}", path.display()),
+                    ));
+                }
+                std::fs::remove_file(path)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            clients: Vec::new(),
+            emulator: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Attaches every client waiting to connect
+    pub fn accept(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => match MirrorClient::new(stream) {
+                    Ok(client) => self.clients.push(client),
+                    Err(e) => tracing::warn!("Failed to attach mirror client: {e}"),
+                },
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    tracing::warn!("Failed to accept mirror client: {e}");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Collects the key events every client sent since the last
+    /// call, resizing clients that reported a new size and
+    /// dropping those that hung up
+    pub fn poll_input(&mut self, delta: f64) -> Vec<Event> {
+        let mut events = Vec::new();
+        self.clients
+            .retain_mut(|client| client.poll_input(delta, &mut events));
+        events
+    }
+
+    /// Draws every client, dropping those that can't be written to
+    pub fn draw(&mut self, mut render: impl FnMut(&mut Frame)) {
+        self.clients
+            .retain_mut(|client| client.terminal.draw(&mut render).is_ok());
+    }
+
+    pub fn clear(&mut self) {
+        for client in &mut self.clients {
+            let _ = client.terminal.clear();
+        }
+    }
+
+    /// Opens a terminal emulator attached to the mirror. `program`
+    /// is run with `args`, where `{command}` in an argument is
+    /// replaced with `attach_command` and `{shader}` with the
+    /// shader path. Arguments mentioning `{shader}` are left out
+    /// when `shader` is empty. `{socket}` in `attach_command` is
+    /// replaced with the socket path
+    pub fn launch(
+        &mut self,
+        program: &str,
+        args: &[String],
+        shader: &str,
+        attach_command: &str,
+    ) -> io::Result<()> {
+        if !shader.is_empty() && !Path::new(shader).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("shader {shader} does not exist"),
+            ));
+        }
+        let command = attach_command.replace("{socket}", &self.path.to_string_lossy());
+        let args = args
+            .iter()
+            .filter(|arg| !shader.is_empty() || !arg.contains("{shader}"))
+            .map(|arg| {
+                arg.replace("{command}", &command)
+                    .replace("{shader}", shader)
+            });
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        self.emulator = Some(child);
+        Ok(())
+    }
+
+    /// Closes the emulator window, returning `false` if
+    /// none was open
+    pub fn close_emulator(&mut self) -> bool {
+        match self.emulator.take() {
+            Some(mut child) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` once if the emulator window was closed
+    /// by the player
+    pub fn emulator_exited(&mut self) -> bool {
+        let exited = self
+            .emulator
+            .as_mut()
+            .is_some_and(|child| !matches!(child.try_wait(), Ok(None)));
+        if exited {
+            self.emulator = None;
+        }
+        exited
+    }
+}
+
+impl Drop for MirrorServer {
+    fn drop(&mut self) {
+        self.close_emulator();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl MirrorClient {
+    fn new(mut stream: UnixStream) -> io::Result<Self> {
+        stream.set_nonblocking(false)?;
+        // A stalled client shouldn't freeze the game
+        stream.set_write_timeout(Some(Duration::from_millis(100)))?;
+        stream.execute(EnterAlternateScreen)?;
+        stream.write_all(SIZE_QUERY)?;
+
+        let socket = stream.try_clone()?;
+        let mut reader = stream.try_clone()?;
+        let (sender, input) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buf = [0; 256];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => {
+                        if sender.send(buf[..read].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        let terminal = ratatui::Terminal::with_options(
+            CrosstermBackend::new(stream),
+            TerminalOptions {
+                viewport: Viewport::Fixed(DEFAULT_SIZE),
+            },
+        )?;
+        Ok(Self {
+            terminal,
+            socket,
+            input,
+            parser: InputParser::default(),
+            since_size_query: 0.0,
+        })
+    }
+
+    /// Returns `false` once the client has hung up
+    fn poll_input(&mut self, delta: f64, events: &mut Vec<Event>) -> bool {
+        let mut inputs = Vec::new();
+        loop {
+            match self.input.try_recv() {
+                Ok(bytes) => inputs.extend(self.parser.feed(&bytes)),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+        inputs.extend(self.parser.tick(delta));
+        for input in inputs {
+            match input {
+                MirrorInput::Event(event) => events.push(event),
+                MirrorInput::Resize { columns, rows } => {
+                    let size = Rect::new(0, 0, columns, rows);
+                    if size != self.terminal.get_frame().area()
+                        && self.terminal.resize(size).is_err()
+                    {
+                        return false;
+                    }
+                }
+            }
+        }
+        self.since_size_query += delta;
+        if self.since_size_query >= SIZE_QUERY_INTERVAL {
+            self.since_size_query = 0.0;
+            let writer = self.terminal.backend_mut();
+            if writer
+                .write_all(SIZE_QUERY)
+                .and_then(|_| writer.flush())
+                .is_err()
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Drop for MirrorClient {
+    fn drop(&mut self) {
+        let _ = self.terminal.backend_mut().execute(LeaveAlternateScreen);
+        let _ = self.socket.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, os::unix::net::UnixListener, path::PathBuf};
+
+    use crate::terminal::mirror::MirrorServer;
+
+    /// A path in the temporary directory no other test uses
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mirror-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn leaves_files_that_are_not_sockets_alone() {
+        let path = temp_path("file");
+        std::fs::write(&path, "save data").unwrap();
+        let error = MirrorServer::bind(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "save data");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn replaces_sockets_left_behind() {
+        let path = temp_path("socket");
+        // Leaves the socket file behind, like a game that crashed
+        drop(UnixListener::bind(&path).unwrap());
+        let server = MirrorServer::bind(&path).unwrap();
+        let error = MirrorServer::bind(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+        drop(server);
+        assert!(!path.exists());
+    }
+}
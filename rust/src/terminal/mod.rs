@@ -1,20 +1,20 @@
 use std::{
-    cell::RefCell,
-    ops::Deref,
+    fmt::Display,
+    io::{ErrorKind, IsTerminal},
     time::Duration,
 };
 
-#[cfg(unix)]
-use std::{os::fd::IntoRawFd, io::PipeWriter, process::{Stdio, Command}};
-
-use ansi_to_tui::IntoText;
-use clap::Parser;
+use clap::{
+    CommandFactory, ValueEnum,
+    builder::{PossibleValuesParser, TypedValueParser},
+};
 use godot::{
-    classes::{InputEvent, InputEventKey},
+    classes::{InputEvent, InputEventKey, RichTextLabel},
     prelude::*,
 };
 use ratatui::{
     DefaultTerminal, Frame,
+    buffer::Buffer,
     crossterm::{
         self,
         event::{
@@ -23,68 +23,87 @@ use ratatui::{
         },
     },
     layout::{Alignment, Constraint, Direction, Layout, Offset, Rect},
-    // prelude::CrosstermBackend,
+    style::{Color, Style},
     text::Text,
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Paragraph, Widget},
 };
-use tachyonfx::{Effect, Interpolation, RefCount, Shader, fx};
+use tachyonfx::{Interpolation, Shader, fx};
 use tracing::instrument;
-use yoke::{Yoke, Yokeable};
 
-use crate::terminal::{
-    commands::{
-        main::{GateOperation, MainCli, MainCommands},
-        pause::{PauseCli, PauseCommands},
+#[cfg(unix)]
+use crate::terminal::mirror::MirrorServer;
+use crate::{
+    LOG_SERVER,
+    terminal::{
+        camera::{CameraFeeds, CameraStatic},
+        color_depth::ColorDepth,
+        commands::{init::Component, main::MainCli, pause::PauseCli},
+        completion::Completion,
+        dispatch::{CommandAction, refusal},
+        doors::{DoorKind, DoorPanel, Doors},
+        effects::{EffectKey, Region, RegionAreas, TerminalEffects},
+        entities::EntityRegistry,
+        history::CommandHistory,
+        line_editor::LineEditor,
+        monitor::MonitorBackend,
+        output::OutputPane,
+        position_marker::PositionMarker,
+        power::{Power, PowerEvent, PowerGauge},
+        subsystems::Subsystems,
+        theme::{TerminalTheme, Theme, ThemePreset},
+        transition::{Transition, ViewTransition},
+        views::Views,
     },
-    cursor_x::CursorX,
-    position_marker::PositionMarker,
-    tile_map::TerminalTileMap,
+    tracing_godot::CoopServer,
 };
 
+mod camera;
+mod color_depth;
 mod commands;
+mod completion;
 mod cursor_x;
+mod dispatch;
+mod doors;
+mod effects;
+mod entities;
+mod history;
+mod line_editor;
+#[cfg(unix)]
+mod mirror;
+mod monitor;
+mod output;
 mod position_marker;
+mod power;
+mod subsystems;
+#[cfg(test)]
+mod tests;
+mod theme;
 mod tile_map;
+mod transition;
+mod views;
 
-#[derive(Yokeable)]
-pub struct YokeableText<'a>(Text<'a>);
-
-#[derive(Default, Clone, Copy)]
-enum TerminalView {
-    #[default]
-    MainView,
-    Room1,
-    Room2,
-    Room3,
-    Room4,
-}
-
-impl From<commands::main::Area> for TerminalView {
-    fn from(value: commands::main::Area) -> Self {
-        match value {
-            commands::main::Area::MainView => Self::MainView,
-            commands::main::Area::Room1 => Self::Room1,
-            commands::main::Area::Room2 => Self::Room2,
-            commands::main::Area::Room3 => Self::Room3,
-            commands::main::Area::Room4 => Self::Room4,
-        }
-    }
-}
+const DEFAULT_HISTORY_CAPACITY: u32 = 100;
+const DEFAULT_DOOR_COUNT: u32 = 4;
+/// Letters and digits that can't be mistaken for each other
+/// when read aloud or off a screen
+const SESSION_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const SESSION_CODE_LENGTH: usize = 6;
 
 pub struct TerminalState {
-    main_tile_map: Option<Gd<TerminalTileMap>>,
-    room_1_tile_map: Option<Gd<TerminalTileMap>>,
-    room_2_tile_map: Option<Gd<TerminalTileMap>>,
-    room_3_tile_map: Option<Gd<TerminalTileMap>>,
-    room_4_tile_map: Option<Gd<TerminalTileMap>>,
-    command: String,
-    command_output: Yoke<YokeableText<'static>, String>,
-    view: RefCount<TerminalView>,
-    cursor_x: CursorX,
-    initialized: bool,
-    effect: Effect,
+    views: Views,
+    line_editor: LineEditor,
+    output: OutputPane,
+    history: CommandHistory,
+    completion: Option<Completion>,
+    subsystems: Subsystems,
+    doors: Doors,
+    power: Power,
+    effects: TerminalEffects,
     paused: bool,
-    player_xy: (f32, f32),
+    entities: EntityRegistry,
+    cameras: CameraFeeds,
+    transition: Option<Transition>,
+    theme: Theme,
 }
 
 #[derive(GodotClass)]
@@ -94,9 +113,107 @@ pub struct TerminalState {
 pub struct Terminal {
     terminal_state: TerminalState,
     base: Base<Node>,
-    terminal: DefaultTerminal,
+    /// The terminal the game was launched from, if it was
+    /// launched from one
+    terminal: Option<DefaultTerminal>,
+    monitor: Option<ratatui::Terminal<MonitorBackend>>,
+    /// `color_depth` with `Auto` worked out, which the host
+    /// terminal is drawn in
+    host_color_depth: ColorDepth,
     #[cfg(unix)]
-    pipe_terminal: Option<ratatui::Terminal<CrosstermBackend<PipeWriter>>>,
+    mirror: Option<MirrorServer>,
+    coop: Option<CoopServer>,
+    /// Where the command history is saved between sessions.
+    /// Leave empty to keep history in memory only
+    #[export]
+    history_file: GString,
+    /// How many commands the history remembers
+    #[export]
+    history_capacity: u32,
+    /// Percent of power drained per second while the
+    /// security system is on
+    #[export]
+    power_idle_drain: f64,
+    /// Extra percent of power drained per second for each door
+    /// the player closed, zoomed in view and initialized component
+    #[export]
+    power_load_drain: f64,
+    /// The child `TerminalTileMap` shown when not zoomed in on
+    /// anything. Every other child becomes a room for `zoom`
+    #[export]
+    main_view: GString,
+    /// How many gates the sewer has, numbered from 1
+    #[export]
+    gate_count: u32,
+    /// How many vents the sewer has, numbered from 1
+    #[export]
+    vent_count: u32,
+    /// Also emit the old `gate_1`..`gate_4` and `vent_1`..`vent_4`
+    /// signals alongside `gate_changed` and `vent_changed`
+    #[export]
+    legacy_door_signals: bool,
+    /// How `zoom` animates between views
+    #[export]
+    view_transition: ViewTransition,
+    /// How long view transitions take, in milliseconds
+    #[export]
+    view_transition_ms: u32,
+    /// The Unix socket other terminals attach to to mirror this
+    /// one. Leave empty to use one in the temporary directory
+    #[export]
+    mirror_socket: GString,
+    /// The terminal emulator Ctrl+Shift+P opens to mirror
+    /// the terminal
+    #[export]
+    mirror_program: GString,
+    /// Arguments for `mirror_program`. `{command}` is replaced with
+    /// `mirror_attach_command` and `{shader}` with `mirror_shader`
+    #[export]
+    mirror_args: PackedStringArray,
+    /// The command the emulator runs to attach to the mirror
+    /// socket, which replaces `{socket}`
+    #[export]
+    mirror_attach_command: GString,
+    /// A shader for the mirror to be drawn with. Arguments
+    /// mentioning `{shader}` are left out when this is empty
+    #[export(file = "*.glsl")]
+    mirror_shader: GString,
+    /// Where the co-op page a second player types commands from
+    /// is served. Use `0.0.0.0:<port>` to let players on other
+    /// machines join. Leave empty to turn co-op off
+    #[export]
+    coop_address: GString,
+    /// The code the second player enters on the co-op page. Leave
+    /// empty to pick a random one. `get_coop_session_code` returns
+    /// it so it can be shown to the player
+    #[export]
+    session_code: GString,
+    /// A label the terminal is drawn into, so it can be shown
+    /// in-world, for example on a monitor through a `SubViewport`.
+    /// Can be assigned while the game runs, or set to `null` before
+    /// the label is freed
+    #[export]
+    #[var(get, set = set_monitor_label)]
+    monitor_label: Option<Gd<RichTextLabel>>,
+    /// The size of the terminal drawn into `monitor_label`,
+    /// in characters
+    #[export]
+    monitor_columns: u32,
+    #[export]
+    monitor_rows: u32,
+    /// Also draw to the terminal the game was launched from,
+    /// when there is one
+    #[export]
+    host_terminal: bool,
+    /// How many colors the host terminal can show
+    #[export]
+    color_depth: ColorDepth,
+    /// The built-in theme the terminal is drawn with
+    #[export]
+    theme_preset: ThemePreset,
+    /// A theme to use instead of `theme_preset`
+    #[export]
+    custom_theme: Option<Gd<TerminalTheme>>,
 }
 
 #[godot_api]
@@ -104,55 +221,184 @@ impl INode for Terminal {
     #[instrument(skip_all)]
     fn init(base: Base<Node>) -> Self {
         Self {
-            terminal_state: TerminalState {
-                main_tile_map: None,
-                room_1_tile_map: None,
-                room_2_tile_map: None,
-                room_3_tile_map: None,
-                room_4_tile_map: None,
-                command: String::new(),
-                command_output: Yoke::attach_to_cart(
-                    String::from("Try typing `help` to get started"),
-                    |cart| YokeableText(Text::from(cart)),
-                ),
-                view: RefCount::new(RefCell::new(TerminalView::default())),
-                cursor_x: CursorX::default(),
-                initialized: false,
-                effect: fx::coalesce((1000, Interpolation::Linear)),
-                paused: false,
-                player_xy: (0.0, 0.0),
-            },
+            terminal_state: TerminalState::new(Views::default()),
             base,
-            terminal: ratatui::init(),
+            terminal: None,
+            monitor: None,
+            host_color_depth: ColorDepth::default(),
             #[cfg(unix)]
-            pipe_terminal: None,
+            mirror: None,
+            coop: None,
+            history_file: GString::from("user://terminal_history.txt"),
+            history_capacity: DEFAULT_HISTORY_CAPACITY,
+            power_idle_drain: 0.1,
+            power_load_drain: 0.05,
+            main_view: GString::from("MainView"),
+            gate_count: DEFAULT_DOOR_COUNT,
+            vent_count: DEFAULT_DOOR_COUNT,
+            legacy_door_signals: true,
+            view_transition: ViewTransition::default(),
+            view_transition_ms: 500,
+            mirror_socket: GString::new(),
+            mirror_program: GString::from("ghostty"),
+            mirror_args: [
+                "--gtk-single-instance=false",
+                "--custom-shader={shader}",
+                "--command={command}",
+            ]
+            .into_iter()
+            .map(GString::from)
+            .collect(),
+            mirror_shader: GString::new(),
+            mirror_attach_command: GString::from("socat -,raw,echo=0 UNIX-CONNECT:{socket}"),
+            coop_address: GString::from("127.0.0.1:8700"),
+            session_code: GString::new(),
+            monitor_label: None,
+            monitor_columns: 100,
+            monitor_rows: 30,
+            host_terminal: true,
+            color_depth: ColorDepth::default(),
+            theme_preset: ThemePreset::default(),
+            custom_theme: None,
         }
     }
 
     #[instrument(skip_all)]
     fn ready(&mut self) {
-        self.terminal_state.main_tile_map = self.base().try_get_node_as("MainView");
-        self.terminal_state.room_1_tile_map = self.base().try_get_node_as("Room1");
-        self.terminal_state.room_2_tile_map = self.base().try_get_node_as("Room2");
-        self.terminal_state.room_3_tile_map = self.base().try_get_node_as("Room3");
-        self.terminal_state.room_4_tile_map = self.base().try_get_node_as("Room4");
-        self.signals().init().connect_self(Self::_on_init);
+        self.apply_theme();
+        if self.host_terminal && std::io::stdout().is_terminal() {
+            self.host_color_depth = self.color_depth.resolve();
+            tracing::info!(
+                "Drawing to the host terminal in {:?}",
+                self.host_color_depth
+            );
+            match ratatui::try_init() {
+                Ok(terminal) => self.terminal = Some(terminal),
+                Err(e) => tracing::warn!("Failed to draw to the host terminal: {e}"),
+            }
+        }
+        self.open_monitor();
+        self.terminal_state
+            .effects
+            .add(Region::Main, fx::coalesce((1000, Interpolation::Linear)));
+        self.terminal_state.views =
+            Views::discover(&self.to_gd().upcast(), &self.main_view.to_string());
+        #[cfg(unix)]
+        {
+            let socket = if self.mirror_socket.is_empty() {
+                std::env::temp_dir().join("fnaf-5-double-vision.sock")
+            } else {
+                std::path::PathBuf::from(
+                    godot::classes::ProjectSettings::singleton()
+                        .globalize_path(&self.mirror_socket)
+                        .to_string(),
+                )
+            };
+            match MirrorServer::bind(&socket) {
+                Ok(mirror) => self.mirror = Some(mirror),
+                Err(e) => tracing::warn!("Failed to open mirror socket {}: {e}", socket.display()),
+            }
+        }
+        if !self.coop_address.is_empty() {
+            self.open_coop();
+        }
+        self.signals()
+            .init_vent()
+            .connect_self(|this| this.boot(Component::Vent));
+        self.signals()
+            .init_camera()
+            .connect_self(|this| this.boot(Component::Camera));
+        self.signals()
+            .init_audio()
+            .connect_self(|this| this.boot(Component::Audio));
         self.signals().pause().connect_self(Self::_on_pause);
+        self.terminal_state.doors = Doors::new(self.gate_count as usize, self.vent_count as usize);
+        self.signals()
+            .gate_changed()
+            .connect_self(|this, id, opened| this.on_door_changed(DoorKind::Gate, id, opened));
+        self.signals()
+            .vent_changed()
+            .connect_self(|this, id, opened| this.on_door_changed(DoorKind::Vent, id, opened));
+        self.terminal_state
+            .history
+            .set_capacity(self.history_capacity as usize);
+        if !self.history_file.is_empty()
+            && let Err(e) = self.terminal_state.history.load(&self.history_file)
+            && e.kind() != ErrorKind::NotFound
+        {
+            tracing::warn!("Failed to load command history: {e}");
+        }
     }
 
     #[instrument(skip_all)]
     fn process(&mut self, delta: f64) {
-        if crossterm::event::poll(Duration::from_secs(0)).unwrap_or_default() {
+        if self.terminal.is_some()
+            && crossterm::event::poll(Duration::from_secs(0)).unwrap_or_default()
+        {
             self.event(crossterm::event::read().unwrap());
         }
-        self.terminal
-            .draw(|frame| self.terminal_state.draw(frame, delta))
-            .unwrap();
         #[cfg(unix)]
-        if let Some(pipe_terminal) = &mut self.pipe_terminal {
-            pipe_terminal
-                .draw(|frame| self.terminal_state.draw(frame, 0.0))
-                .unwrap();
+        if let Some(mirror) = &mut self.mirror {
+            mirror.accept();
+            let events = mirror.poll_input(delta);
+            if mirror.emulator_exited() {
+                self.terminal_state
+                    .output
+                    .push("Mirror terminal closed".to_string());
+            }
+            for event in events {
+                self.event(event);
+            }
+        }
+        let remote_commands = self
+            .coop
+            .as_ref()
+            .map(CoopServer::take_commands)
+            .unwrap_or_default();
+        for command in remote_commands {
+            self.remote_command(command);
+        }
+        if !self.terminal_state.paused {
+            self.terminal_state.cameras.tick(delta);
+        }
+        if !self.terminal_state.paused && self.terminal_state.subsystems.any_online() {
+            let rate = self.power_idle_drain
+                + self.power_load_drain * self.terminal_state.power_load() as f64;
+            match self.terminal_state.power.drain(rate, delta) {
+                Some(PowerEvent::Changed(level)) => self.signals().power_changed().emit(level),
+                Some(PowerEvent::Out) => {
+                    self.signals().power_changed().emit(0.0);
+                    self.signals().power_out().emit();
+                }
+                None => {}
+            }
+        }
+        // The first screen drawn is the primary one, which advances
+        // effects and decides how far the prompt and output scroll
+        let mut delta = Some(delta);
+        if let Some(terminal) = &mut self.terminal
+            && let Err(e) = terminal.draw(|frame| {
+                self.terminal_state.draw(frame, delta.take());
+                // Only the host terminal is limited in colors
+                share_frame(self.coop.as_ref(), frame.buffer_mut());
+                self.host_color_depth.quantize(frame.buffer_mut());
+            })
+        {
+            tracing::warn!("Failed to draw to the host terminal: {e}");
+        }
+        if let Some(monitor) = &mut self.monitor {
+            let spectated = delta.is_some();
+            match monitor.draw(|frame| self.terminal_state.draw(frame, delta.take())) {
+                Ok(completed_frame) if spectated => {
+                    share_frame(self.coop.as_ref(), completed_frame.buffer)
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to draw to the monitor: {e}"),
+            }
+        }
+        #[cfg(unix)]
+        if let Some(mirror) = &mut self.mirror {
+            mirror.draw(|frame| self.terminal_state.draw(frame, delta.take()));
         }
     }
 
@@ -341,16 +587,51 @@ impl INode for Terminal {
 
     #[instrument(skip_all)]
     fn exit_tree(&mut self) {
-        ratatui::restore();
+        if !self.history_file.is_empty()
+            && let Err(e) = self.terminal_state.history.save(&self.history_file)
+        {
+            tracing::warn!("Failed to save command history: {e}");
+        }
+        #[cfg(unix)]
+        {
+            self.mirror = None;
+        }
+        self.coop = None;
+        if self.terminal.take().is_some() {
+            ratatui::restore();
+        }
     }
 }
 
 #[godot_api]
 impl Terminal {
+    /// Emitted when the first component of the security
+    /// system comes online
     #[signal]
     fn init();
     #[signal]
+    fn init_vent();
+    #[signal]
+    fn init_camera();
+    #[signal]
+    fn init_audio();
+    #[signal]
     fn pause(state: bool);
+    #[signal]
+    fn power_changed(level: f64);
+    #[signal]
+    fn power_out();
+    /// Emitted when `zoom` switches views, with the name of the
+    /// view now being watched (`main-view`, `room1`, ...)
+    #[signal]
+    fn camera_changed(view: GString);
+
+    /// Emitted when a gate is opened or closed from the terminal
+    #[signal]
+    fn gate_changed(id: u32, opened: bool);
+    /// Emitted when a vent is opened or closed from the terminal
+    #[signal]
+    fn vent_changed(id: u32, opened: bool);
 
     #[signal]
     fn gate_1(opened: bool);
@@ -372,26 +653,164 @@ impl Terminal {
     #[func]
     #[instrument(skip(self))]
     fn _on_player_pos_changed(&mut self, x: f32, y: f32) {
-        self.terminal_state.player_xy = (x, y);
+        self.terminal_state.entities.set_position("player", x, y);
         tracing::info!("Player pos changed");
     }
 
+    /// Moves a tracked entity (`player`, `rat`, `possum` or
+    /// `raccoon`) to a new world position on the terminal map
+    #[func]
+    #[instrument(skip(self))]
+    fn _on_entity_pos_changed(&mut self, id: GString, x: f32, y: f32) {
+        if !self
+            .terminal_state
+            .entities
+            .set_position(&id.to_string(), x, y)
+        {
+            tracing::warn!("No tracked entity with id {id}");
+        }
+    }
+
+    /// Fills a view's camera feed with static for `seconds`,
+    /// hiding everything in it
+    #[func]
+    #[instrument(skip(self))]
+    fn disrupt_camera(&mut self, view: GString, seconds: f64) {
+        let view = view.to_string();
+        if self.terminal_state.views.contains(&view) {
+            self.terminal_state.cameras.disrupt(&view, seconds);
+        } else {
+            tracing::warn!("No view named {view} to disrupt");
+        }
+    }
+
+    /// Returns the name of the view currently being watched
+    #[func]
+    fn get_watched_view(&self) -> GString {
+        GString::from(self.terminal_state.views.current())
+    }
+
+    /// Flashes the whole terminal red and glitches it, for
+    /// when an enemy gets the player
+    #[func]
+    fn play_jumpscare_glitch(&mut self) {
+        self.terminal_state.effects.add_unique(
+            EffectKey::Jumpscare,
+            Region::Screen,
+            effects::jumpscare_glitch(),
+        );
+    }
+
+    /// Flickers the map, doors and power gauge as if the
+    /// power is dipping
+    #[func]
+    fn play_power_flicker(&mut self) {
+        self.terminal_state.effects.add_unique(
+            EffectKey::PowerFlicker,
+            Region::Main,
+            effects::power_flicker(),
+        );
+    }
+
+    /// Bursts static over the map for `seconds`, like an enemy
+    /// brushing past a camera
+    #[func]
+    fn play_camera_static(&mut self, seconds: f64) {
+        self.terminal_state.effects.add_unique(
+            EffectKey::CameraStatic,
+            Region::Map,
+            CameraStatic::burst((seconds * 1000.0) as u32),
+        );
+    }
+
+    /// Returns the path of the socket other terminals can attach
+    /// to to mirror this one, or an empty string if there is none
+    #[func]
+    fn get_mirror_socket_path(&self) -> GString {
+        #[cfg(unix)]
+        if let Some(mirror) = &self.mirror {
+            return GString::from(mirror.path().to_string_lossy().as_ref());
+        }
+        GString::new()
+    }
+
+    #[func]
+    fn set_monitor_label(&mut self, label: Option<Gd<RichTextLabel>>) {
+        self.monitor_label = label;
+        if self.base().is_node_ready() {
+            self.open_monitor();
+        }
+    }
+
+    /// Returns the code the second player enters on the co-op
+    /// page, or an empty string if co-op is off
+    #[func]
+    fn get_coop_session_code(&self) -> GString {
+        if self.coop.is_some() {
+            return self.session_code.clone();
+        }
+        GString::new()
+    }
+
+    /// Redraws the terminal with `custom_theme`, or `theme_preset`
+    /// if there is no custom theme. Call after changing either
+    #[func]
+    fn apply_theme(&mut self) {
+        let theme = match &self.custom_theme {
+            Some(theme) => theme.bind().theme(),
+            None => self.theme_preset.theme(),
+        };
+        self.terminal_state.set_theme(theme);
+    }
+
+    /// Brings every component online at once, as `init` did
+    /// before components could be booted separately
     #[func]
     fn _on_init(&mut self) {
-        self.terminal_state.effect = fx::coalesce((1000, Interpolation::Linear));
-        self.terminal_state.initialized = true;
+        for component in Component::value_variants() {
+            self.boot(*component);
+        }
     }
 
     #[func]
     fn _on_pause(&mut self, state: bool) {
         self.terminal_state.paused = state;
     }
+
+    /// Returns whether the given gate is open, as far as
+    /// the terminal knows
+    #[func]
+    fn is_gate_open(&self, gate: u32) -> bool {
+        self.terminal_state
+            .doors
+            .is_open(DoorKind::Gate, gate as usize)
+            .unwrap_or_default()
+    }
+
+    /// Returns whether the given vent is open, as far as
+    /// the terminal knows
+    #[func]
+    fn is_vent_open(&self, vent: u32) -> bool {
+        self.terminal_state
+            .doors
+            .is_open(DoorKind::Vent, vent as usize)
+            .unwrap_or_default()
+    }
 }
 
 impl Terminal {
     #[instrument(skip(self))]
     fn event(&mut self, event: crossterm::event::Event) {
         tracing::debug!("New Event");
+        if let event::Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event
+            && !matches!(code, KeyCode::Tab | KeyCode::BackTab | KeyCode::Modifier(_))
+        {
+            self.terminal_state.completion = None;
+        }
         match event {
             #[cfg(unix)]
             event::Event::Key(KeyEvent {
@@ -400,14 +819,7 @@ impl Terminal {
                 modifiers,
                 ..
             }) if modifiers == KeyModifiers::CONTROL | KeyModifiers::SHIFT => {
-                let (pipe_reader, pipe_writer) = std::io::pipe().unwrap();
-                Command::new("ghostty").args([
-                    "--gtk-single-instance=false",
-                    "--custom-shader=/home/isaacm/PersonalProjects/ghostty-shaders/glitchy.glsl",
-                    format!("--command=cat /proc/{}/fd/{}", std::process::id(), pipe_reader.into_raw_fd()).as_str(),
-                ]).stdout(Stdio::null()).stderr(Stdio::null()).spawn().unwrap();
-                self.pipe_terminal =
-                    Some(ratatui::Terminal::new(CrosstermBackend::new(pipe_writer)).unwrap());
+                self.toggle_mirror();
             }
             event::Event::Key(KeyEvent {
                 code: KeyCode::Char('c' | 'C'),
@@ -415,174 +827,408 @@ impl Terminal {
                 modifiers,
                 ..
             }) if modifiers == KeyModifiers::CONTROL | KeyModifiers::SHIFT => {
-                self.terminal.clear().unwrap();
+                if let Some(terminal) = &mut self.terminal {
+                    terminal.clear().unwrap();
+                }
+                if let Some(monitor) = &mut self.monitor {
+                    let _ = monitor.clear();
+                }
                 #[cfg(unix)]
-                if let Some(pipe_terminal) = &mut self.pipe_terminal {
-                    pipe_terminal.clear().unwrap();
+                if let Some(mirror) = &mut self.mirror {
+                    mirror.clear();
                 }
             }
             event::Event::Key(KeyEvent {
-                code: KeyCode::Char(c),
+                code: code @ (KeyCode::Tab | KeyCode::BackTab),
                 kind: KeyEventKind::Press,
+                modifiers,
                 ..
             }) => {
-                self.terminal_state
-                    .command
-                    .insert(self.terminal_state.cursor_x.byte(), c);
-                self.terminal_state.cursor_x.incr_by_char(c);
+                self.complete(code == KeyCode::Tab && !modifiers.contains(KeyModifiers::SHIFT));
             }
             event::Event::Key(KeyEvent {
-                code: KeyCode::Left,
+                code: KeyCode::Up,
                 kind: KeyEventKind::Press,
                 ..
             }) => {
-                if let Some(char) = self
-                    .terminal_state
-                    .command
-                    .chars()
-                    .nth(self.terminal_state.cursor_x.character().saturating_sub(1))
+                let terminal_state = &mut self.terminal_state;
+                if let Some(command) = terminal_state
+                    .history
+                    .previous(terminal_state.line_editor.text())
                 {
-                    self.terminal_state.cursor_x.decr_by_char(char);
+                    terminal_state.line_editor.set(command);
                 }
             }
             event::Event::Key(KeyEvent {
-                code: KeyCode::Right,
+                code: KeyCode::Down,
                 kind: KeyEventKind::Press,
                 ..
             }) => {
-                if let Some(char) = self
-                    .terminal_state
-                    .command
-                    .chars()
-                    .nth(self.terminal_state.cursor_x.character())
+                let terminal_state = &mut self.terminal_state;
+                if let Some(command) = terminal_state
+                    .history
+                    .next(terminal_state.line_editor.text())
                 {
-                    self.terminal_state.cursor_x.incr_by_char(char);
+                    terminal_state.line_editor.set(command);
                 }
             }
             event::Event::Key(KeyEvent {
-                code: KeyCode::Enter,
+                code: KeyCode::PageUp,
                 kind: KeyEventKind::Press,
                 ..
             }) => {
-                self.terminal_state.cursor_x = CursorX::default();
-                self.command();
+                self.terminal_state.output.page_up();
             }
             event::Event::Key(KeyEvent {
-                code: KeyCode::Backspace,
-                modifiers: KeyModifiers::CONTROL,
+                code: KeyCode::PageDown,
                 kind: KeyEventKind::Press,
                 ..
             }) => {
-                self.terminal_state.cursor_x = CursorX::default();
-                self.terminal_state.command.clear();
+                self.terminal_state.output.page_down();
             }
             event::Event::Key(KeyEvent {
-                code: KeyCode::Backspace,
+                code: KeyCode::Enter,
                 kind: KeyEventKind::Press,
                 ..
             }) => {
-                let character_x = self.terminal_state.cursor_x.character();
-                if character_x == 0 {
+                self.command();
+            }
+            event::Event::Key(key) => {
+                self.terminal_state.line_editor.key(key);
+            }
+            _ => {}
+        }
+    }
+
+    /// Opens the mirror terminal, or closes it if it's open
+    #[cfg(unix)]
+    fn toggle_mirror(&mut self) {
+        let Some(mirror) = &mut self.mirror else {
+            self.refuse("the mirror socket couldn't be opened");
+            return;
+        };
+        if mirror.close_emulator() {
+            self.terminal_state
+                .output
+                .push("Mirror terminal closed".to_string());
+            return;
+        }
+        let args = self
+            .mirror_args
+            .as_slice()
+            .iter()
+            .map(GString::to_string)
+            .collect::<Vec<_>>();
+        let shader = if self.mirror_shader.is_empty() {
+            String::new()
+        } else {
+            godot::classes::ProjectSettings::singleton()
+                .globalize_path(&self.mirror_shader)
+                .to_string()
+        };
+        match mirror.launch(
+            &self.mirror_program.to_string(),
+            &args,
+            &shader,
+            &self.mirror_attach_command.to_string(),
+        ) {
+            Ok(()) => {
+                self.terminal_state
+                    .output
+                    .push("Mirror terminal opened".to_string());
+            }
+            Err(e) => self.refuse(format!(
+                "couldn't open the mirror terminal `{}`: {e}",
+                self.mirror_program
+            )),
+        }
+    }
+
+    fn complete(&mut self, forward: bool) {
+        let terminal_state = &mut self.terminal_state;
+        match &mut terminal_state.completion {
+            Some(completion) => completion.cycle(forward),
+            None => {
+                let command = if terminal_state.paused {
+                    PauseCli::command()
+                } else {
+                    terminal_state.main_cli()
+                };
+                let line = terminal_state.line_editor.before_cursor();
+                let Some(completion) = Completion::new(command, line, |_| Vec::new()) else {
                     return;
+                };
+                if completion.is_ambiguous() {
+                    terminal_state
+                        .output
+                        .push(completion.candidates().join("  "));
                 }
-                if let Some((index, char)) = self
-                    .terminal_state
-                    .command
-                    .char_indices()
-                    .nth(character_x.saturating_sub(1))
-                {
-                    self.terminal_state.command.remove(index);
-                    self.terminal_state.cursor_x.decr_by_char(char);
-                }
+                terminal_state.completion = Some(completion);
             }
-            _ => {}
         }
+        let Some(completion) = &terminal_state.completion else {
+            return;
+        };
+        let word_start = completion.word_start();
+        let replacement = completion.current();
+        if !completion.is_ambiguous() {
+            terminal_state.completion = None;
+        }
+        let cursor_byte = terminal_state.line_editor.cursor_x().byte();
+        terminal_state
+            .line_editor
+            .replace_range(word_start..cursor_byte, &replacement);
     }
 
     fn command(&mut self) {
-        if let Some(command) = shlex::split(&self.terminal_state.command) {
-            if !self.terminal_state.paused {
-                self.main_command(command);
-            } else {
-                self.pause_command(command);
+        let command = self.terminal_state.line_editor.take();
+        self.terminal_state.history.push(&command);
+        self.terminal_state.output.echo(command.clone());
+        self.run(&command);
+    }
+
+    /// Runs a command the co-op player sent. It is echoed like a
+    /// local one, so both players see it, but left out of history.
+    /// Its output is also sent back to the co-op page
+    fn remote_command(&mut self, command: String) {
+        self.terminal_state.output.echo_remote(command.clone());
+        for action in self.actions(&command) {
+            if let Some(coop) = &self.coop {
+                match &action {
+                    CommandAction::Output(output) => coop.send_output(output, false),
+                    CommandAction::Error(error) => coop.send_output(error, true),
+                    _ => {}
+                }
             }
+            self.apply(action);
         }
-        self.terminal_state.command.clear();
     }
 
-    fn main_command(&mut self, command: Vec<String>) {
-        let cli = MainCli::try_parse_from(command);
-        match cli {
-            Ok(command) => {
-                self.terminal_state.clear_output();
-                match command.command {
-                    MainCommands::Init => {
-                        if !self.terminal_state.initialized {
-                            self.signals().init().emit();
-                        }
-                    }
-                    MainCommands::Pause => self.signals().pause().emit(true),
-                    MainCommands::Gate(gate_args) => {
-                        let opened = match gate_args.operation {
-                            GateOperation::Open => true,
-                            GateOperation::Close => false,
-                        };
-                        match gate_args.gate {
-                            1 => self.signals().gate_1().emit(opened),
-                            2 => self.signals().gate_2().emit(opened),
-                            3 => self.signals().gate_3().emit(opened),
-                            _ => self.signals().gate_4().emit(opened),
-                        }
-                    }
-                    MainCommands::Vent(gate_args) => {
-                        let opened = match gate_args.operation {
-                            GateOperation::Open => true,
-                            GateOperation::Close => false,
-                        };
-                        match gate_args.gate {
-                            1 => self.signals().vent_1().emit(opened),
-                            2 => self.signals().vent_2().emit(opened),
-                            3 => self.signals().vent_3().emit(opened),
-                            _ => self.signals().vent_4().emit(opened),
-                        }
-                    }
-                    MainCommands::Zoom { area } => {
-                        *self.terminal_state.view.borrow_mut() = area.into();
-                    }
+    fn run(&mut self, command: &str) {
+        for action in self.actions(command) {
+            self.apply(action);
+        }
+    }
+
+    fn actions(&self, command: &str) -> Vec<CommandAction> {
+        shlex::split(command)
+            .map(|command| self.terminal_state.dispatch(command))
+            .unwrap_or_default()
+    }
+
+    /// Starts drawing into `monitor_label`, if there is one
+    fn open_monitor(&mut self) {
+        self.monitor = None;
+        if let Some(label) = self.monitor_label.clone() {
+            let backend =
+                MonitorBackend::new(label, self.monitor_columns as u16, self.monitor_rows as u16);
+            match ratatui::Terminal::new(backend) {
+                Ok(monitor) => self.monitor = Some(monitor),
+                Err(e) => tracing::warn!("Failed to draw to the monitor: {e}"),
+            }
+        }
+    }
+
+    /// Serves the co-op page on `coop_address`
+    fn open_coop(&mut self) {
+        if self.session_code.is_empty() {
+            match random_session_code() {
+                Ok(session_code) => self.session_code = session_code.into(),
+                Err(e) => {
+                    tracing::warn!("Failed to pick a co-op session code: {e}");
+                    return;
                 }
             }
-            Err(e) => {
-                self.terminal_state
-                    .set_output(e.render().ansi().to_string());
+        }
+        match CoopServer::bind(
+            &self.coop_address.to_string(),
+            self.session_code.to_string(),
+        ) {
+            Ok(coop) => {
+                tracing::info!("Serving the co-op page on {}", self.coop_address);
+                self.coop = Some(coop);
             }
+            Err(e) => tracing::warn!(
+                "Failed to serve the co-op page on {}: {e}",
+                self.coop_address
+            ),
         }
     }
 
-    fn pause_command(&mut self, command: Vec<String>) {
-        let cli = PauseCli::try_parse_from(command);
-        match cli {
-            Ok(command) => {
-                self.terminal_state.clear_output();
-                match command.command {
-                    PauseCommands::UnPause => self.signals().pause().emit(false),
+    /// Carries out what a command asked for
+    fn apply(&mut self, action: CommandAction) {
+        match action {
+            CommandAction::Output(output) => self.terminal_state.output.push(output),
+            CommandAction::Error(error) => self.terminal_state.output.push_error(error),
+            CommandAction::StartSystem => self.signals().init().emit(),
+            CommandAction::Init(Component::Vent) => self.signals().init_vent().emit(),
+            CommandAction::Init(Component::Camera) => self.signals().init_camera().emit(),
+            CommandAction::Init(Component::Audio) => self.signals().init_audio().emit(),
+            CommandAction::Pause(state) => self.signals().pause().emit(state),
+            CommandAction::SetDoor { kind, id, opened } => self.set_door(kind, id, opened),
+            CommandAction::Zoom { area } => {
+                let from = self.terminal_state.views.current().to_string();
+                if self.terminal_state.views.set_current(&area) {
+                    self.terminal_state.transition = self
+                        .view_transition
+                        .effect(self.view_transition_ms)
+                        .map(|effect| Transition { from, effect });
+                    self.signals()
+                        .camera_changed()
+                        .emit(&GString::from(area.as_str()));
                 }
             }
-            Err(e) => {
-                self.terminal_state
-                    .set_output(e.render().ansi().to_string());
-            }
+        }
+    }
+
+    /// Shows why something was refused in the output pane
+    fn refuse(&mut self, message: impl Display) {
+        self.apply(refusal(message));
+    }
+
+    /// Asks Godot to open or close a door
+    fn set_door(&mut self, kind: DoorKind, id: u32, opened: bool) {
+        match kind {
+            DoorKind::Gate => self.signals().gate_changed().emit(id, opened),
+            DoorKind::Vent => self.signals().vent_changed().emit(id, opened),
+        }
+        if !self.legacy_door_signals {
+            return;
+        }
+        match (kind, id) {
+            (DoorKind::Gate, 1) => self.signals().gate_1().emit(opened),
+            (DoorKind::Gate, 2) => self.signals().gate_2().emit(opened),
+            (DoorKind::Gate, 3) => self.signals().gate_3().emit(opened),
+            (DoorKind::Gate, 4) => self.signals().gate_4().emit(opened),
+            (DoorKind::Vent, 1) => self.signals().vent_1().emit(opened),
+            (DoorKind::Vent, 2) => self.signals().vent_2().emit(opened),
+            (DoorKind::Vent, 3) => self.signals().vent_3().emit(opened),
+            (DoorKind::Vent, 4) => self.signals().vent_4().emit(opened),
+            _ => {}
+        }
+    }
+
+    fn on_door_changed(&mut self, kind: DoorKind, id: u32, opened: bool) {
+        self.terminal_state.doors.set(kind, id as usize, opened);
+    }
+
+    fn boot(&mut self, component: Component) {
+        if self.terminal_state.subsystems.bring_online(component) {
+            self.terminal_state
+                .effects
+                .add(Region::Main, component.boot_effect());
+            self.terminal_state
+                .output
+                .push(component.boot_message().to_string());
         }
     }
 }
 
 impl TerminalState {
-    fn draw(&mut self, frame: &mut Frame, delta: f64) {
+    fn new(views: Views) -> Self {
+        Self {
+            views,
+            line_editor: LineEditor::default(),
+            output: OutputPane::new("Try typing `help` to get started"),
+            history: CommandHistory::new(DEFAULT_HISTORY_CAPACITY as usize),
+            completion: None,
+            subsystems: Subsystems::default(),
+            doors: Doors::new(DEFAULT_DOOR_COUNT as usize, DEFAULT_DOOR_COUNT as usize),
+            power: Power::default(),
+            effects: TerminalEffects::default(),
+            paused: false,
+            entities: EntityRegistry::default(),
+            cameras: CameraFeeds::default(),
+            transition: None,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Draws the view called `name`, centered in `map_area`, along
+    /// with every entity its camera can see
+    fn render_view(&mut self, name: &str, map_area: Rect, buf: &mut Buffer) {
+        let Some(tile_map) = self.views.tile_map(name) else {
+            return;
+        };
+        let used_rect = tile_map.used_rect();
+        let center = Layout::new(Direction::Horizontal, [Constraint::Length(used_rect.width)])
+            .flex(ratatui::layout::Flex::Center)
+            .split(Rect {
+                x: map_area.x,
+                y: map_area.y,
+                width: map_area.width,
+                height: used_rect.height,
+            })[0];
+        if self.cameras.is_disrupted(name) {
+            CameraStatic(self.cameras.rng()).render(center, buf);
+            return;
+        }
+        tile_map.render_map(center, buf, self.theme.map);
+        let bounds = tile_map.bounds();
+        let zoomed = !self.views.is_main(name);
+        // A room camera only sees what's inside the room
+        for entity in self.entities.iter().filter(|entity| {
+            entity.is_visible(zoomed)
+                && (!zoomed || entity.position.is_some_and(|(x, y)| bounds.contains(x, y)))
+        }) {
+            let color = match self.theme.marker {
+                Color::Reset => entity.color,
+                color => color,
+            };
+            PositionMarker(entity, bounds, color).render(center, buf);
+        }
+    }
+
+    /// The main command line, with `zoom` limited to the views
+    /// found in the scene and `gate`/`vent` to the doors that exist
+    fn main_cli(&self) -> clap::Command {
+        let views = self.views.names().map(str::to_string).collect::<Vec<_>>();
+        let door_ids = |kind| {
+            let ids = (1..=self.doors.count(kind)).map(|id| id.to_string());
+            PossibleValuesParser::new(ids).map(|id| id.parse::<u32>().unwrap_or_default())
+        };
+        MainCli::command()
+            .mut_subcommand("zoom", |zoom| {
+                zoom.mut_arg("area", |area| {
+                    area.value_parser(PossibleValuesParser::new(views))
+                })
+            })
+            .mut_subcommand("gate", |gate| {
+                gate.mut_arg("gate", |id| id.value_parser(door_ids(DoorKind::Gate)))
+            })
+            .mut_subcommand("vent", |vent| {
+                vent.mut_arg("gate", |id| id.value_parser(door_ids(DoorKind::Vent)))
+            })
+    }
+
+    /// How many power hungry things are running: doors the player
+    /// closed, a zoomed in view and initialized components
+    fn power_load(&self) -> usize {
+        let zoomed = self.views.is_zoomed();
+        self.doors.held_count() + zoomed as usize + self.subsystems.online_count()
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.output.set_theme(theme);
+    }
+
+    /// Draws the terminal into `frame`. The primary screen passes
+    /// `Some(delta)`, advancing effects and transitions by `delta`
+    /// seconds and fitting scrolling to its layout. Other screens
+    /// pass `None` and show the same moment at their own size
+    fn draw(&mut self, frame: &mut Frame, delta: Option<f64>) {
         let area = frame.area();
+        if self.theme.background != Color::Reset {
+            frame
+                .buffer_mut()
+                .set_style(area, Style::new().bg(self.theme.background));
+        }
         let [main_area, error_area, terminal_area]: [Rect; 3] = Layout::new(
             Direction::Vertical,
             [
                 Constraint::Min(0),
-                Constraint::Length(self.command_output.get().0.height() as u16 + 2),
+                Constraint::Length(self.output.height(area.height / 3)),
                 Constraint::Length(3),
             ],
         )
@@ -590,17 +1236,31 @@ impl TerminalState {
         .as_ref()
         .try_into()
         .unwrap();
+        let prompt_width = terminal_area.width.saturating_sub(2) as usize;
+        if delta.is_some() {
+            self.line_editor.scroll_to_fit(prompt_width);
+            self.output.set_page_height(error_area.height);
+        }
+        let (prompt, cursor_column) = self.line_editor.viewport(prompt_width);
         frame.render_widget(
-            Paragraph::new(self.command.as_str()).block(Block::new().borders(Borders::all())),
+            Paragraph::new(prompt)
+                .style(Style::new().fg(self.theme.prompt))
+                .block(
+                    Block::new()
+                        .borders(Borders::all())
+                        .border_style(self.theme.border_style()),
+                ),
             terminal_area,
         );
-        frame.render_widget(
-            Paragraph::new(self.command_output.get().0.clone())
-                .block(Block::new().borders(Borders::all())),
-            error_area,
-        );
+        frame.render_widget(&self.output, error_area);
+        let mut areas = RegionAreas::default();
+        areas.set(Region::Screen, area);
+        areas.set(Region::Main, main_area);
+        areas.set(Region::Map, main_area);
+        areas.set(Region::Output, error_area);
+        areas.set(Region::Prompt, terminal_area);
         frame.set_cursor_position(terminal_area.offset(Offset {
-            x: self.cursor_x.column() as i32 + 1,
+            x: cursor_column as i32 + 1,
             y: 1,
         }));
         if self.paused {
@@ -612,51 +1272,98 @@ impl TerminalState {
                 paused_text.height() as u16,
             );
             frame.render_widget(
-                Paragraph::new(paused_text).alignment(Alignment::Center),
+                Paragraph::new(paused_text)
+                    .style(Style::new().fg(self.theme.paused))
+                    .alignment(Alignment::Center),
                 center,
             );
         } else {
-            if self.initialized {
-                let tile_map = match *self.view.borrow() {
-                    TerminalView::MainView => &self.main_tile_map,
-                    TerminalView::Room1 => &self.room_1_tile_map,
-                    TerminalView::Room2 => &self.room_2_tile_map,
-                    TerminalView::Room3 => &self.room_3_tile_map,
-                    TerminalView::Room4 => &self.room_4_tile_map,
-                };
-                if let Some(tile_map) = tile_map {
-                    let tile_map = tile_map.bind();
-                    let used_rect = tile_map.get_used_rect();
-                    let center =
-                        Layout::new(Direction::Horizontal, [Constraint::Length(used_rect.width)])
-                            .flex(ratatui::layout::Flex::Center)
-                            .split(Rect {
-                                x: main_area.x,
-                                y: main_area.y,
-                                width: main_area.width,
-                                height: used_rect.height,
-                            })[0];
-                    frame.render_widget(tile_map.deref(), center);
-                    frame.render_widget(
-                        PositionMarker(self.player_xy.0, self.player_xy.1, tile_map),
-                        center,
-                    );
+            let [map_area, side_area]: [Rect; 2] = Layout::new(
+                Direction::Horizontal,
+                [Constraint::Min(0), Constraint::Length(DoorPanel::WIDTH)],
+            )
+            .split(main_area)
+            .as_ref()
+            .try_into()
+            .unwrap();
+            let [door_area, power_area]: [Rect; 2] = Layout::new(
+                Direction::Vertical,
+                [Constraint::Min(0), Constraint::Length(PowerGauge::HEIGHT)],
+            )
+            .split(side_area)
+            .as_ref()
+            .try_into()
+            .unwrap();
+            areas.set(Region::Map, map_area);
+            frame.render_widget(DoorPanel(&self.doors, &self.theme), door_area);
+            frame.render_widget(PowerGauge(&self.power, &self.theme), power_area);
+            if self.subsystems.is_online(Component::Camera) && !self.power.is_out() {
+                let current = self.views.current().to_string();
+                self.render_view(&current, map_area, frame.buffer_mut());
+                if let Some(mut transition) = self.transition.take() {
+                    // Draw the old view on top of the new one, so the
+                    // new one shows wherever the old one is wiped away
+                    let mut outgoing = Buffer::empty(map_area);
+                    self.render_view(&transition.from, map_area, &mut outgoing);
+                    match delta {
+                        Some(delta) => transition.effect.process(
+                            Duration::from_secs_f64(delta),
+                            &mut outgoing,
+                            map_area,
+                        ),
+                        None => transition.effect.clone().process(
+                            Duration::ZERO,
+                            &mut outgoing,
+                            map_area,
+                        ),
+                    };
+                    let buf = frame.buffer_mut();
+                    for position in map_area.positions() {
+                        if outgoing[position].symbol() != " " {
+                            buf[position] = outgoing[position].clone();
+                        }
+                    }
+                    if !transition.effect.done() {
+                        self.transition = Some(transition);
+                    }
                 }
             }
-            self.effect.process(
-                Duration::from_secs_f64(delta),
-                frame.buffer_mut(),
-                main_area,
-            );
+        }
+        match delta {
+            Some(delta) => self.effects.process(delta, &areas, frame.buffer_mut()),
+            None => self.effects.draw_copy(&areas, frame.buffer_mut()),
         }
     }
+}
 
-    fn set_output(&mut self, output: String) {
-        self.command_output =
-            Yoke::attach_to_cart(output, |f| YokeableText(f.into_text().unwrap()));
+/// Shows a frame to anyone spectating and to the co-op player
+fn share_frame(coop: Option<&CoopServer>, buffer: &Buffer) {
+    if let Some(log_server) = LOG_SERVER.get() {
+        log_server.send_frame(buffer);
     }
+    if let Some(coop) = coop {
+        coop.send_frame(buffer);
+    }
+}
 
-    fn clear_output(&mut self) {
-        self.command_output = Yoke::attach_to_cart(String::new(), |f| YokeableText(Text::from(f)));
+/// Picks a code from the OS's random number generator, so it
+/// can't be predicted from the game's own randomness
+fn random_session_code() -> Result<String, getrandom::Error> {
+    // Bytes past the last whole multiple of the alphabet's length
+    // are thrown away, so every letter is as likely
+    let limit = 256 - 256 % SESSION_CODE_ALPHABET.len();
+    let mut session_code = String::with_capacity(SESSION_CODE_LENGTH);
+    let mut bytes = [0; SESSION_CODE_LENGTH * 2];
+    while session_code.len() < SESSION_CODE_LENGTH {
+        getrandom::fill(&mut bytes)?;
+        for byte in bytes
+            .iter()
+            .map(|byte| *byte as usize)
+            .filter(|byte| *byte < limit)
+            .take(SESSION_CODE_LENGTH - session_code.len())
+        {
+            session_code.push(SESSION_CODE_ALPHABET[byte % SESSION_CODE_ALPHABET.len()] as char);
+        }
     }
+    Ok(session_code)
 }
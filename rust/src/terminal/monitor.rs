@@ -0,0 +1,199 @@
+use std::{fmt::Write, io};
+
+use godot::{classes::RichTextLabel, prelude::*};
+use ratatui::{
+    backend::{Backend, WindowSize},
+    buffer::{Buffer, Cell},
+    layout::{Position, Rect, Size},
+    style::Modifier,
+};
+
+use crate::palette::{LOG_PAGE, LOG_PAGE_BACKGROUND, LOG_PAGE_FOREGROUND, Rgb, to_rgb};
+
+/// Draws the terminal into a `RichTextLabel` instead of the host
+/// TTY, so it can be shown inside the game, for example on a
+/// monitor by putting the label in a `SubViewport`. The label
+/// should use a monospace font
+pub struct MonitorBackend {
+    label: Gd<RichTextLabel>,
+    screen: Buffer,
+    cursor: Position,
+    cursor_visible: bool,
+}
+
+impl MonitorBackend {
+    pub fn new(mut label: Gd<RichTextLabel>, columns: u16, rows: u16) -> Self {
+        label.set_use_bbcode(true);
+        label.set_scroll_active(false);
+        label.set_autowrap_mode(godot::classes::text_server::AutowrapMode::OFF);
+        Self {
+            label,
+            screen: Buffer::empty(Rect::new(0, 0, columns, rows)),
+            cursor: Position::ORIGIN,
+            cursor_visible: false,
+        }
+    }
+
+    /// The screen as BBCode, with runs of identically
+    /// styled cells sharing their tags
+    fn bbcode(&self) -> String {
+        let mut bbcode = String::new();
+        let area = self.screen.area;
+        for y in area.top()..area.bottom() {
+            if y > area.top() {
+                bbcode.push('\n');
+            }
+            let mut run: Option<(Style, String)> = None;
+            for x in area.left()..area.right() {
+                let cell = &self.screen[(x, y)];
+                let cursor = self.cursor_visible && self.cursor == Position::new(x, y);
+                let style = Style::of(cell, cursor);
+                match &mut run {
+                    Some((run_style, text)) if *run_style == style => push_symbol(text, cell),
+                    _ => {
+                        if let Some((run_style, text)) = run.take() {
+                            run_style.write(&mut bbcode, &text);
+                        }
+                        let mut text = String::new();
+                        push_symbol(&mut text, cell);
+                        run = Some((style, text));
+                    }
+                }
+            }
+            if let Some((run_style, text)) = run {
+                run_style.write(&mut bbcode, &text);
+            }
+        }
+        bbcode
+    }
+}
+
+impl Backend for MonitorBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        for (x, y, cell) in content {
+            if let Some(target) = self.screen.cell_mut((x, y)) {
+                *target = cell.clone();
+            }
+        }
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = false;
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.cursor_visible = true;
+        Ok(())
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        Ok(self.cursor)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.cursor = position.into();
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.screen.reset();
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        Ok(self.screen.area.as_size())
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        Ok(WindowSize {
+            columns_rows: self.size()?,
+            pixels: Size::default(),
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let bbcode = self.bbcode();
+        self.label.set_text(&bbcode);
+        Ok(())
+    }
+}
+
+/// How a run of cells is drawn, resolved to concrete colors
+#[derive(PartialEq)]
+struct Style {
+    fg: Rgb,
+    bg: Rgb,
+    modifier: Modifier,
+}
+
+impl Style {
+    fn of(cell: &Cell, cursor: bool) -> Self {
+        let mut fg = to_rgb(cell.fg, &LOG_PAGE).unwrap_or(LOG_PAGE_FOREGROUND);
+        let mut bg = to_rgb(cell.bg, &LOG_PAGE).unwrap_or(LOG_PAGE_BACKGROUND);
+        // The cursor is drawn as a block over its cell
+        if cell.modifier.contains(Modifier::REVERSED) != cursor {
+            (fg, bg) = (bg, fg);
+        }
+        Self {
+            fg,
+            bg,
+            modifier: cell.modifier,
+        }
+    }
+
+    fn write(&self, bbcode: &mut String, text: &str) {
+        let (r, g, b) = self.fg;
+        let alpha = if self.modifier.contains(Modifier::DIM) {
+            0x99
+        } else {
+            0xFF
+        };
+        let _ = write!(bbcode, "[color=#{r:02x}{g:02x}{b:02x}{alpha:02x}]");
+        if self.bg != LOG_PAGE_BACKGROUND {
+            let (r, g, b) = self.bg;
+            let _ = write!(bbcode, "[bgcolor=#{r:02x}{g:02x}{b:02x}]");
+        }
+        let tags = [
+            (Modifier::SLOW_BLINK, "pulse freq=1.0"),
+            (Modifier::RAPID_BLINK, "pulse freq=2.5"),
+            (Modifier::BOLD, "b"),
+            (Modifier::ITALIC, "i"),
+            (Modifier::UNDERLINED, "u"),
+            (Modifier::CROSSED_OUT, "s"),
+        ];
+        let tags = tags
+            .iter()
+            .filter(|(modifier, _)| self.modifier.contains(*modifier));
+        for (_, tag) in tags.clone() {
+            let _ = write!(bbcode, "[{tag}]");
+        }
+        bbcode.push_str(text);
+        for (_, tag) in tags.rev() {
+            let name = tag.split(' ').next().unwrap_or(tag);
+            let _ = write!(bbcode, "[/{name}]");
+        }
+        if self.bg != LOG_PAGE_BACKGROUND {
+            bbcode.push_str("[/bgcolor]");
+        }
+        bbcode.push_str("[/color]");
+    }
+}
+
+fn push_symbol(text: &mut String, cell: &Cell) {
+    if cell.modifier.contains(Modifier::HIDDEN) {
+        text.push(' ');
+        return;
+    }
+    for char in cell.symbol().chars() {
+        match char {
+            '[' => text.push_str("[lb]"),
+            ']' => text.push_str("[rb]"),
+            char => text.push(char),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+use ansi_to_tui::IntoText;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+use tracing::instrument;
+use yoke::{Yoke, Yokeable};
+
+use crate::terminal::theme::Theme;
+
+/// How many lines of output the scrollback keeps
+const SCROLLBACK_LINES: usize = 1000;
+
+#[derive(Yokeable)]
+pub struct YokeableText<'a>(Text<'a>);
+
+struct Entry {
+    text: Yoke<YokeableText<'static>, String>,
+    /// Drawn in the theme's error color
+    error: bool,
+}
+
+/// The command output pane, keeping a scrollback buffer of
+/// previously entered commands and their output
+pub struct OutputPane {
+    entries: VecDeque<Entry>,
+    /// The number of lines in `entries`
+    line_count: usize,
+    /// How many lines the view is scrolled up from the newest output
+    scroll: usize,
+    /// The number of lines visible on the primary screen, used
+    /// as the PageUp/PageDown step
+    page_height: usize,
+    theme: Theme,
+}
+
+impl OutputPane {
+    pub fn new(greeting: &str) -> Self {
+        let mut output_pane = Self {
+            entries: VecDeque::new(),
+            line_count: 0,
+            scroll: 0,
+            page_height: 1,
+            theme: Theme::default(),
+        };
+        output_pane.push_entry(
+            Yoke::attach_to_cart(greeting.to_string(), |cart| YokeableText(Text::from(cart))),
+            false,
+        );
+        output_pane
+    }
+
+    /// Appends command output, which may contain ANSI escapes
+    pub fn push(&mut self, output: String) {
+        if output.trim().is_empty() {
+            return;
+        }
+        self.push_entry(
+            Yoke::attach_to_cart(output, |cart| YokeableText(cart.into_text().unwrap())),
+            false,
+        );
+    }
+
+    /// Appends an error, shown in the theme's error color
+    pub fn push_error(&mut self, error: String) {
+        self.push_entry(
+            Yoke::attach_to_cart(error, |cart| YokeableText(Text::from(cart.trim_end()))),
+            true,
+        );
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Appends the echo of a command entered at the prompt
+    pub fn echo(&mut self, command: String) {
+        self.push_entry(
+            Yoke::attach_to_cart(command, |cart| {
+                YokeableText(Text::from(Line::from(vec![
+                    Span::raw("> ").dim(),
+                    Span::raw(cart),
+                ])))
+            }),
+            false,
+        );
+    }
+
+    /// Appends the echo of a command the co-op player sent
+    pub fn echo_remote(&mut self, command: String) {
+        self.push_entry(
+            Yoke::attach_to_cart(command, |cart| {
+                YokeableText(Text::from(Line::from(vec![
+                    Span::raw("co-op> ").magenta().dim(),
+                    Span::raw(cart),
+                ])))
+            }),
+            false,
+        );
+    }
+
+    pub fn page_up(&mut self) {
+        self.scroll = (self.scroll + self.page_height).min(self.max_scroll(self.page_height));
+    }
+
+    pub fn page_down(&mut self) {
+        self.scroll = self.scroll.saturating_sub(self.page_height);
+    }
+
+    /// Fits scrolling to a pane `area_height` rows tall, borders
+    /// included
+    pub fn set_page_height(&mut self, area_height: u16) {
+        self.page_height = page_height(area_height);
+        self.scroll = self.scroll.min(self.max_scroll(self.page_height));
+    }
+
+    /// The height of the pane, borders included, when it may take
+    /// up at most `max_height` rows
+    pub fn height(&self, max_height: u16) -> u16 {
+        (self.line_count as u16 + 2).min(max_height.max(3))
+    }
+
+    fn push_entry(&mut self, text: Yoke<YokeableText<'static>, String>, error: bool) {
+        self.line_count += text.get().0.height();
+        self.entries.push_back(Entry { text, error });
+        self.scroll = 0;
+        while self.line_count > SCROLLBACK_LINES
+            && let Some(oldest) = self.entries.front_mut()
+        {
+            let excess = self.line_count - SCROLLBACK_LINES;
+            let height = oldest.text.get().0.height();
+            if height <= excess {
+                self.entries.pop_front();
+                self.line_count -= height;
+            } else {
+                oldest.text.with_mut(move |text| {
+                    text.0.lines.drain(..excess);
+                });
+                self.line_count -= excess;
+            }
+        }
+    }
+
+    /// The `height` lines from `top` down
+    fn visible_lines(&self, top: usize, height: usize) -> impl Iterator<Item = Line<'_>> {
+        let error_style = Style::new().fg(self.theme.error);
+        self.entries
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .text
+                    .get()
+                    .0
+                    .lines
+                    .iter()
+                    .map(|line| (line, entry.error))
+            })
+            .skip(top)
+            .take(height)
+            .map(move |(line, error)| {
+                if error {
+                    line.clone().patch_style(error_style)
+                } else {
+                    line.clone()
+                }
+            })
+    }
+
+    fn max_scroll(&self, height: usize) -> usize {
+        self.line_count.saturating_sub(height)
+    }
+}
+
+/// The lines that fit in a pane `area_height` rows tall
+fn page_height(area_height: u16) -> usize {
+    area_height.saturating_sub(2).max(1) as usize
+}
+
+impl Widget for &OutputPane {
+    #[instrument(skip(self, buf))]
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let height = page_height(area.height);
+        let scroll = self.scroll.min(self.max_scroll(height));
+        let top = self.max_scroll(height) - scroll;
+        let text = Text::from_iter(self.visible_lines(top, height));
+        let mut block = Block::new()
+            .borders(Borders::all())
+            .border_style(self.theme.border_style());
+        if scroll > 0 {
+            block = block.title_bottom(Line::from(format!(" ↓ {scroll} ")).right_aligned());
+        }
+        Paragraph::new(text)
+            .style(Style::new().fg(self.theme.output))
+            .block(block)
+            .render(area, buf);
+    }
+}
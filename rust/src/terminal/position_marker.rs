@@ -1,12 +1,14 @@
-use godot::obj::GdRef;
 use ratatui::prelude::*;
 use ratatui::widgets::Widget;
 use tracing::instrument;
 
 use crate::inverse_lerp;
-use crate::terminal::tile_map::TerminalTileMap;
+use crate::terminal::entities::TrackedEntity;
+use crate::terminal::tile_map::MapBounds;
 
-pub struct PositionMarker<'a>(pub f32, pub f32, pub GdRef<'a, TerminalTileMap>);
+/// Draws an entity on a map covering `MapBounds`, in the
+/// given color
+pub struct PositionMarker<'a>(pub &'a TrackedEntity, pub MapBounds, pub Color);
 
 impl<'a> Widget for PositionMarker<'a> {
     #[instrument(skip(self, buf))]
@@ -14,12 +16,16 @@ impl<'a> Widget for PositionMarker<'a> {
     where
         Self: Sized,
     {
-        let Some(x) = inverse_lerp(self.2.get_pos_left()..=self.2.get_pos_right(), self.0) else {
+        let Some((x, y)) = self.0.position else {
+            return;
+        };
+
+        let Some(x) = inverse_lerp(self.1.left..=self.1.right, x) else {
             return;
         };
         let x = x * area.width as f32;
 
-        let Some(y) = inverse_lerp(self.2.get_pos_top()..=self.2.get_pos_bottom(), self.1) else {
+        let Some(y) = inverse_lerp(self.1.top..=self.1.bottom, y) else {
             return;
         };
         let y = y * area.height as f32;
@@ -28,6 +34,6 @@ impl<'a> Widget for PositionMarker<'a> {
         let y = y as u16 + area.y;
 
         buf.cell_mut((x, y))
-            .map(|cell| cell.set_symbol("@").set_fg(Color::Cyan));
+            .map(|cell| cell.set_symbol(self.0.glyph).set_fg(self.2));
     }
 }
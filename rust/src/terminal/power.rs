@@ -0,0 +1,101 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    widgets::{Block, Borders, Gauge, Widget},
+};
+use tracing::instrument;
+
+use crate::terminal::theme::Theme;
+
+pub enum PowerEvent {
+    /// The displayed power percentage changed
+    Changed(f64),
+    /// Power just ran out
+    Out,
+}
+
+/// The security system's remaining power, as a percentage
+pub struct Power {
+    level: f64,
+    last_reported: u32,
+}
+
+impl Default for Power {
+    fn default() -> Self {
+        Self {
+            level: Self::FULL,
+            last_reported: Self::FULL as u32,
+        }
+    }
+}
+
+impl Power {
+    pub const FULL: f64 = 100.0;
+
+    pub fn level(&self) -> f64 {
+        self.level
+    }
+
+    pub fn is_out(&self) -> bool {
+        self.level <= 0.0
+    }
+
+    /// Drains `rate` percent per second for `delta` seconds. Only
+    /// reports a change when the whole percentage drops, so Godot
+    /// isn't signalled every frame
+    pub fn drain(&mut self, rate: f64, delta: f64) -> Option<PowerEvent> {
+        if self.is_out() {
+            return None;
+        }
+        self.level = (self.level - rate * delta).max(0.0);
+        if self.is_out() {
+            self.last_reported = 0;
+            return Some(PowerEvent::Out);
+        }
+        let percent = self.level.ceil() as u32;
+        if percent != self.last_reported {
+            self.last_reported = percent;
+            Some(PowerEvent::Changed(self.level))
+        } else {
+            None
+        }
+    }
+}
+
+/// A gauge showing the remaining power, drawn in the colors
+/// of a theme
+pub struct PowerGauge<'a>(pub &'a Power, pub &'a Theme);
+
+impl PowerGauge<'_> {
+    pub const HEIGHT: u16 = 3;
+}
+
+impl Widget for PowerGauge<'_> {
+    #[instrument(skip_all)]
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let level = self.0.level();
+        let theme = self.1;
+        let color = if level > 50.0 {
+            theme.ok
+        } else if level > 20.0 {
+            theme.warn
+        } else {
+            theme.error
+        };
+        Gauge::default()
+            .block(
+                Block::new()
+                    .borders(Borders::all())
+                    .border_style(theme.border_style())
+                    .title("Power"),
+            )
+            .gauge_style(Style::new().fg(color))
+            .ratio(level / Power::FULL)
+            .label(format!("{:.0}%", level.ceil()))
+            .render(area, buf);
+    }
+}
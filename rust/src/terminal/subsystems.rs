@@ -0,0 +1,76 @@
+use clap::ValueEnum;
+use ratatui::style::Color;
+use tachyonfx::{Effect, Interpolation, Motion, fx};
+
+use crate::terminal::commands::init::Component;
+
+/// Which components of the sewer security system have
+/// been brought online with `init`
+#[derive(Default)]
+pub struct Subsystems {
+    vent: bool,
+    camera: bool,
+    audio: bool,
+}
+
+impl Subsystems {
+    pub fn is_online(&self, component: Component) -> bool {
+        match component {
+            Component::Vent => self.vent,
+            Component::Camera => self.camera,
+            Component::Audio => self.audio,
+        }
+    }
+
+    pub fn any_online(&self) -> bool {
+        self.vent || self.camera || self.audio
+    }
+
+    pub fn online_count(&self) -> usize {
+        [self.vent, self.camera, self.audio]
+            .into_iter()
+            .filter(|online| *online)
+            .count()
+    }
+
+    /// Marks `component` as online, returning `false` if it
+    /// already was
+    pub fn bring_online(&mut self, component: Component) -> bool {
+        let online = match component {
+            Component::Vent => &mut self.vent,
+            Component::Camera => &mut self.camera,
+            Component::Audio => &mut self.audio,
+        };
+        !std::mem::replace(online, true)
+    }
+}
+
+impl Component {
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn boot_message(self) -> &'static str {
+        match self {
+            Component::Vent => "Vent controls online. Vents now respond to `vent`",
+            Component::Camera => "Camera feeds online. Use `zoom` to inspect an area",
+            Component::Audio => "Audio monitoring online",
+        }
+    }
+
+    pub fn boot_effect(self) -> Effect {
+        match self {
+            Component::Vent => fx::sweep_in(
+                Motion::LeftToRight,
+                10,
+                0,
+                Color::Black,
+                (800, Interpolation::QuadOut),
+            ),
+            Component::Camera => fx::coalesce((1000, Interpolation::Linear)),
+            Component::Audio => fx::fade_from_fg(Color::Green, (600, Interpolation::SineOut)),
+        }
+    }
+}
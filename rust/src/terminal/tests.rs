@@ -0,0 +1,284 @@
+use ratatui::{Terminal, backend::TestBackend, buffer::Buffer, layout::Rect, style::Color};
+
+use crate::terminal::{
+    TerminalState,
+    commands::init::Component,
+    doors::DoorKind,
+    theme::ThemePreset,
+    tile_map::{MapBounds, tile_fg},
+    views::{ViewMap, Views},
+};
+
+/// A map drawn from text, standing in for a `TerminalTileMap`
+struct TextMap {
+    tiles: Buffer,
+    bounds: MapBounds,
+}
+
+impl TextMap {
+    fn boxed(lines: &[&str], bounds: MapBounds) -> Box<dyn ViewMap> {
+        Box::new(Self {
+            tiles: Buffer::with_lines(lines.iter().copied()),
+            bounds,
+        })
+    }
+}
+
+impl ViewMap for TextMap {
+    fn used_rect(&self) -> Rect {
+        self.tiles.area
+    }
+
+    fn bounds(&self) -> MapBounds {
+        self.bounds
+    }
+
+    fn render_map(&self, area: Rect, buf: &mut Buffer, theme_map: Color) {
+        for position in self.tiles.area.positions() {
+            let target = (area.x + position.x, area.y + position.y);
+            if let Some(cell) = buf.cell_mut(target) {
+                *cell = self.tiles[position].clone();
+                cell.fg = tile_fg(cell.fg, Color::Reset, theme_map);
+            }
+        }
+    }
+}
+
+/// Draws `state` once and returns the screen as text
+fn draw(state: &mut TerminalState, width: u16, height: u16) -> Vec<String> {
+    draw_screen(state, width, height, Some(0.0))
+}
+
+/// Draws `state` on a screen other than the primary one
+fn draw_copy(state: &mut TerminalState, width: u16, height: u16) -> Vec<String> {
+    draw_screen(state, width, height, None)
+}
+
+fn draw_screen(
+    state: &mut TerminalState,
+    width: u16,
+    height: u16,
+    delta: Option<f64>,
+) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|frame| state.draw(frame, delta)).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect()
+        })
+        .collect()
+}
+
+/// A main view and a single room
+pub(super) fn sewer() -> Views {
+    Views::new(
+        vec![
+            (
+                "main-view".to_string(),
+                TextMap::boxed(
+                    &["┌──┐", "│  │", "└──┘"],
+                    MapBounds {
+                        left: 0.0,
+                        top: 0.0,
+                        right: 4.0,
+                        bottom: 3.0,
+                    },
+                ),
+            ),
+            (
+                "room1".to_string(),
+                TextMap::boxed(
+                    &["┌┐", "└┘"],
+                    MapBounds {
+                        left: 0.0,
+                        top: 0.0,
+                        right: 2.0,
+                        bottom: 2.0,
+                    },
+                ),
+            ),
+        ],
+        "MainView",
+    )
+}
+
+#[test]
+fn prompt_shows_the_line_being_typed() {
+    let mut state = TerminalState::new(sewer());
+    state.line_editor.set("zoom main-view");
+    let screen = draw(&mut state, 40, 12);
+    assert_eq!(
+        screen[9..],
+        [
+            "┌──────────────────────────────────────┐",
+            "│zoom main-view                        │",
+            "└──────────────────────────────────────┘",
+        ]
+    );
+}
+
+#[test]
+fn prompt_without_room_for_text_still_draws() {
+    let mut state = TerminalState::new(sewer());
+    state.line_editor.set("zoom main-view");
+    let screen = draw(&mut state, 2, 12);
+    assert_eq!(screen[9..], ["┌┐", "││", "└┘"]);
+}
+
+#[test]
+fn output_pane_shows_echoed_commands_and_their_output() {
+    let mut state = TerminalState::new(sewer());
+    state.output.echo("status".to_string());
+    state
+        .output
+        .push("The camera system is offline".to_string());
+    let screen = draw(&mut state, 40, 16);
+    assert_eq!(
+        screen[8..13],
+        [
+            "┌──────────────────────────────────────┐",
+            "│Try typing `help` to get started      │",
+            "│> status                              │",
+            "│The camera system is offline          │",
+            "└──────────────────────────────────────┘",
+        ]
+    );
+}
+
+#[test]
+fn scrollback_keeps_the_newest_thousand_lines() {
+    let mut state = TerminalState::new(sewer());
+    let lines = (0..1500).map(|line| format!("line {line}"));
+    state.output.push(lines.collect::<Vec<_>>().join("\n"));
+    let screen = draw(&mut state, 20, 16);
+    assert_eq!(
+        screen[9..12],
+        [
+            "│line 1497         │",
+            "│line 1498         │",
+            "│line 1499         │"
+        ]
+    );
+    for _ in 0..1000 {
+        state.output.page_up();
+    }
+    let screen = draw(&mut state, 20, 16);
+    assert_eq!(
+        screen[9..12],
+        [
+            "│line 500          │",
+            "│line 501          │",
+            "│line 502          │"
+        ]
+    );
+}
+
+#[test]
+fn other_screens_leave_the_primary_layout_alone() {
+    let mut state = TerminalState::new(sewer());
+    let lines = (0..30).map(|line| format!("line {line}"));
+    state.output.push(lines.collect::<Vec<_>>().join("\n"));
+    state.line_editor.set("gate 1 open && gate 2 open");
+    let primary = draw(&mut state, 20, 16);
+    let copy = draw_copy(&mut state, 12, 60);
+    assert_eq!(copy[58], "│…e 2 open │");
+    assert_eq!(draw(&mut state, 20, 16), primary);
+    state.output.page_up();
+    draw_copy(&mut state, 12, 60);
+    let screen = draw(&mut state, 20, 16);
+    assert_eq!(
+        screen[9..12],
+        [
+            "│line 24           │",
+            "│line 25           │",
+            "│line 26           │"
+        ]
+    );
+}
+
+#[test]
+fn paused_screen_hides_the_map_and_panels() {
+    let mut state = TerminalState::new(sewer());
+    state.subsystems.bring_online(Component::Camera);
+    state.paused = true;
+    let screen = draw(&mut state, 40, 12);
+    assert_eq!(
+        screen[..6],
+        [
+            "                                        ",
+            "                                        ",
+            "                                        ",
+            "                 Paused                 ",
+            "                                        ",
+            "                                        ",
+        ]
+    );
+}
+
+#[test]
+fn tile_map_is_centered_beside_the_panels() {
+    let mut state = TerminalState::new(sewer());
+    state.subsystems.bring_online(Component::Camera);
+    let screen = draw(&mut state, 40, 12);
+    assert_eq!(
+        screen[..6],
+        [
+            "          ┌──┐          ┌Doors─────────┐",
+            "          │  │          │gate 1  CLOSED│",
+            "          └──┘          └──────────────┘",
+            "                        ┌Power─────────┐",
+            "                        │█████100% ████│",
+            "                        └──────────────┘",
+        ]
+    );
+}
+
+#[test]
+fn position_marker_is_placed_by_world_position() {
+    let mut state = TerminalState::new(sewer());
+    state.subsystems.bring_online(Component::Camera);
+    state.entities.set_position("player", 2.0, 1.0);
+    // The rat only shows up on room cameras
+    state.entities.set_position("rat", 1.0, 1.0);
+    let screen = draw(&mut state, 40, 12);
+    assert_eq!(
+        screen[..3]
+            .iter()
+            .map(|line| line.chars().take(14).collect::<String>())
+            .collect::<Vec<_>>(),
+        ["          ┌──┐", "          │ @│", "          └──┘"]
+    );
+}
+
+#[test]
+fn theme_recolors_borders_map_markers_and_panels() {
+    let mut state = TerminalState::new(sewer());
+    state.subsystems.bring_online(Component::Camera);
+    state.entities.set_position("player", 2.0, 1.0);
+    let theme = ThemePreset::GreenPhosphor.theme();
+    state.set_theme(theme);
+    let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+    terminal.draw(|frame| state.draw(frame, Some(0.0))).unwrap();
+    let buffer = terminal.backend().buffer();
+    assert_eq!(buffer[(10, 0)].fg, theme.map);
+    assert_eq!(buffer[(12, 1)].fg, theme.marker);
+    assert_eq!(buffer[(0, 11)].fg, theme.border);
+    assert_eq!(buffer[(5, 5)].bg, theme.background);
+    assert_eq!(buffer[(33, 1)].fg, theme.error);
+    assert_eq!(buffer[(25, 4)].fg, theme.ok);
+}
+
+#[test]
+fn doors_only_draw_power_once_the_player_closes_them() {
+    let mut state = TerminalState::new(sewer());
+    state.subsystems.bring_online(Component::Vent);
+    assert_eq!(state.power_load(), 1);
+    state.doors.set(DoorKind::Gate, 1, false);
+    state.doors.set(DoorKind::Vent, 2, false);
+    assert_eq!(state.power_load(), 3);
+    state.doors.set(DoorKind::Gate, 1, true);
+    assert_eq!(state.power_load(), 2);
+}
@@ -0,0 +1,170 @@
+use godot::{builtin::Color as GodotColor, prelude::*};
+use ratatui::style::{Color, Style};
+
+/// The colors the terminal is drawn with. `Color::Reset` keeps
+/// the host terminal's default, or for `marker` and `map`, the
+/// colors entities and tile layers pick themselves
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub background: Color,
+    pub border: Color,
+    pub prompt: Color,
+    pub output: Color,
+    pub error: Color,
+    pub paused: Color,
+    /// Open doors and a healthy power level. Closed doors and
+    /// low power use `error`
+    pub ok: Color,
+    /// Power running low
+    pub warn: Color,
+    pub marker: Color,
+    pub map: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: Color::Reset,
+            border: Color::Reset,
+            prompt: Color::Reset,
+            output: Color::Reset,
+            error: Color::Red,
+            paused: Color::Reset,
+            ok: Color::Green,
+            warn: Color::Yellow,
+            marker: Color::Reset,
+            map: Color::Reset,
+        }
+    }
+}
+
+impl Theme {
+    pub fn border_style(&self) -> Style {
+        Style::new().fg(self.border)
+    }
+}
+
+/// The themes the terminal comes with
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, Debug)]
+#[godot(via = GString)]
+pub enum ThemePreset {
+    /// The host terminal's own colors
+    #[default]
+    Default,
+    /// Amber text on a dark brown screen
+    AmberCrt,
+    /// Green text on a black screen
+    GreenPhosphor,
+    /// Bright, clearly distinct colors on black
+    HighContrast,
+}
+
+impl ThemePreset {
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemePreset::Default => Theme::default(),
+            ThemePreset::AmberCrt => Theme {
+                background: Color::Rgb(0x1A, 0x10, 0x00),
+                border: Color::Rgb(0xB3, 0x6B, 0x00),
+                prompt: Color::Rgb(0xFF, 0xB0, 0x00),
+                output: Color::Rgb(0xFF, 0xB0, 0x00),
+                error: Color::Rgb(0xFF, 0x6A, 0x00),
+                paused: Color::Rgb(0xFF, 0xCC, 0x66),
+                ok: Color::Rgb(0xFF, 0xE0, 0xA0),
+                warn: Color::Rgb(0xFF, 0xB0, 0x00),
+                marker: Color::Rgb(0xFF, 0xE0, 0xA0),
+                map: Color::Rgb(0xCC, 0x8A, 0x00),
+            },
+            ThemePreset::GreenPhosphor => Theme {
+                background: Color::Rgb(0x00, 0x12, 0x00),
+                border: Color::Rgb(0x1F, 0x9F, 0x1F),
+                prompt: Color::Rgb(0x33, 0xFF, 0x33),
+                output: Color::Rgb(0x33, 0xFF, 0x33),
+                error: Color::Rgb(0xCC, 0xFF, 0xCC),
+                paused: Color::Rgb(0x99, 0xFF, 0x99),
+                ok: Color::Rgb(0x33, 0xFF, 0x33),
+                warn: Color::Rgb(0x99, 0xFF, 0x99),
+                marker: Color::Rgb(0xCC, 0xFF, 0xCC),
+                map: Color::Rgb(0x22, 0xCC, 0x22),
+            },
+            ThemePreset::HighContrast => Theme {
+                background: Color::Rgb(0x00, 0x00, 0x00),
+                border: Color::Rgb(0xFF, 0xFF, 0xFF),
+                prompt: Color::Rgb(0xFF, 0xFF, 0xFF),
+                output: Color::Rgb(0xFF, 0xFF, 0xFF),
+                error: Color::Rgb(0xFF, 0x55, 0x55),
+                paused: Color::Rgb(0xFF, 0xFF, 0x00),
+                ok: Color::Rgb(0x00, 0xFF, 0x00),
+                warn: Color::Rgb(0xFF, 0xFF, 0x00),
+                marker: Color::Rgb(0x00, 0xFF, 0xFF),
+                map: Color::Rgb(0xFF, 0xFF, 0xFF),
+            },
+        }
+    }
+}
+
+/// A theme made in the Godot editor and saved as a resource.
+/// A fully transparent color means the same as `Color::Reset`
+#[derive(GodotClass)]
+#[class(init, base=Resource)]
+pub struct TerminalTheme {
+    base: Base<Resource>,
+    #[export]
+    #[init(val = GodotColor::TRANSPARENT_BLACK)]
+    background: GodotColor,
+    #[export]
+    #[init(val = GodotColor::TRANSPARENT_BLACK)]
+    border: GodotColor,
+    #[export]
+    #[init(val = GodotColor::TRANSPARENT_BLACK)]
+    prompt: GodotColor,
+    #[export]
+    #[init(val = GodotColor::TRANSPARENT_BLACK)]
+    output: GodotColor,
+    #[export]
+    #[init(val = GodotColor::from_rgb(0.8, 0.0, 0.0))]
+    error: GodotColor,
+    #[export]
+    #[init(val = GodotColor::TRANSPARENT_BLACK)]
+    paused: GodotColor,
+    #[export]
+    #[init(val = GodotColor::from_rgb(0.0, 0.8, 0.0))]
+    ok: GodotColor,
+    #[export]
+    #[init(val = GodotColor::from_rgb(0.8, 0.8, 0.0))]
+    warn: GodotColor,
+    /// Replaces every entity's own color
+    #[export]
+    #[init(val = GodotColor::TRANSPARENT_BLACK)]
+    marker: GodotColor,
+    /// Replaces every tile layer's `modulate`. Tiles with an `fg`
+    /// color of their own keep it
+    #[export]
+    #[init(val = GodotColor::TRANSPARENT_BLACK)]
+    map: GodotColor,
+}
+
+impl TerminalTheme {
+    pub fn theme(&self) -> Theme {
+        Theme {
+            background: color(self.background),
+            border: color(self.border),
+            prompt: color(self.prompt),
+            output: color(self.output),
+            error: color(self.error),
+            paused: color(self.paused),
+            ok: color(self.ok),
+            warn: color(self.warn),
+            marker: color(self.marker),
+            map: color(self.map),
+        }
+    }
+}
+
+fn color(color: GodotColor) -> Color {
+    if color.a == 0.0 {
+        Color::Reset
+    } else {
+        Color::Rgb(color.r8(), color.g8(), color.b8())
+    }
+}
@@ -1,16 +1,30 @@
 use godot::{
-    classes::{ITileMapLayer, TileMapLayer},
+    classes::{ITileMapLayer, TileData, TileMapLayer, TileSet},
     prelude::*,
 };
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     widgets::Widget,
 };
 use tracing::instrument;
+use unicode_width::UnicodeWidthStr;
 
-const TILESET: [&'static str; 28] = [
+/// The custom data layer on a `TileSet` holding each
+/// tile's glyph, as a string
+const GLYPH_LAYER: &str = "glyph";
+/// Custom data layers holding a tile's foreground and background
+/// colors. Transparent colors fall back to the layer's `modulate`
+/// and the terminal's background
+const FG_LAYER: &str = "fg";
+const BG_LAYER: &str = "bg";
+/// The custom data layer holding a tile's text attributes, as a
+/// string of names like `bold, blink`
+const MODIFIERS_LAYER: &str = "modifiers";
+/// Glyphs for the first atlas row, indexed by atlas x, for tile
+/// sets made before glyphs could be configured
+const LEGACY_GLYPHS: [&str; 28] = [
     "#", "|", "─", "└", "─", "┘", "┌", "┐", "│", "├", "┤", "┬", "┴", "┼", "░", "▉", "┃", "━", "┗",
     "┛", "┏", "┓", "┣", "┫", "┳", "┻", "╋", "▉",
 ];
@@ -19,7 +33,19 @@ const TILESET: [&'static str; 28] = [
 #[class(base=TileMapLayer)]
 struct TerminalTileMapLayer {
     base: Base<TileMapLayer>,
+    /// The layer's tiles. Tiles without a color of their own are
+    /// left at `Color::Reset`, see `tile_fg`
     buffer: Buffer,
+    /// The layer's `modulate` when its cells were last updated
+    modulate: Color,
+    /// Glyphs for this layer only, taking priority over the tile
+    /// set's `glyph` custom data. Keys are `Vector3i(x, y, source)`
+    /// for a tile of one atlas source, or `Vector2i(x, y)` for a
+    /// tile of any source. Glyphs have to be a single character
+    /// one cell wide
+    #[export]
+    #[var(get, set = set_glyphs)]
+    glyphs: Dictionary,
 }
 
 #[godot_api]
@@ -29,6 +55,8 @@ impl ITileMapLayer for TerminalTileMapLayer {
         Self {
             base,
             buffer: Buffer::empty(Rect::new(0, 0, 1, 1)),
+            modulate: Color::Reset,
+            glyphs: Dictionary::new(),
         }
     }
 
@@ -52,38 +80,239 @@ impl ITileMapLayer for TerminalTileMapLayer {
             tiles = base.get_used_cells();
         }
         let modulate = base.get_modulate();
-        let layer_style = Style::new().fg(Color::Rgb(modulate.r8(), modulate.g8(), modulate.b8()));
+        self.modulate = Color::Rgb(modulate.r8(), modulate.g8(), modulate.b8());
+        let layers = CustomDataLayers::of(base.get_tile_set());
         for coordinate in tiles.iter_shared() {
+            let tile_data = layers
+                .any()
+                .then(|| base.get_cell_tile_data(coordinate))
+                .flatten();
+            let glyph = self.glyph(&base, coordinate, &tile_data, &layers);
+            let style = tile_style(&tile_data, &layers);
             if let Some(cell) = self
                 .buffer
                 .cell_mut((coordinate.x as u16, coordinate.y as u16))
             {
-                let cell_atlas_x = base.get_cell_atlas_coords(coordinate).x as usize;
-                cell.set_symbol(TILESET.get(cell_atlas_x).unwrap_or(&" "));
-                cell.set_style(layer_style);
+                cell.set_symbol(&glyph);
+                cell.set_style(style);
+            }
+        }
+    }
+}
+
+#[godot_api]
+impl TerminalTileMapLayer {
+    #[func]
+    fn set_glyphs(&mut self, glyphs: Dictionary) {
+        for (key, glyph) in glyphs.iter_shared() {
+            if !glyph
+                .try_to::<GString>()
+                .is_ok_and(|glyph| is_glyph(&glyph.to_string()))
+            {
+                tracing::warn!("Ignoring glyph {glyph} for {key}, glyphs have to be one cell wide");
             }
         }
+        self.glyphs = glyphs;
+        self.update_cells(Array::new(), true);
+    }
+
+    /// The glyph drawn for the tile at `coordinate`
+    fn glyph(
+        &self,
+        base: &Gd<TileMapLayer>,
+        coordinate: Vector2i,
+        tile_data: &Option<Gd<TileData>>,
+        layers: &CustomDataLayers,
+    ) -> String {
+        let source = base.get_cell_source_id(coordinate);
+        if source == -1 {
+            return " ".to_string();
+        }
+        let atlas = base.get_cell_atlas_coords(coordinate);
+        let layer_glyph = |key: Variant| {
+            self.glyphs
+                .get(key)
+                .and_then(|glyph| glyph.try_to::<GString>().ok())
+                .map(|glyph| glyph.to_string())
+        };
+        GlyphSources {
+            source: layer_glyph(Vector3i::new(atlas.x, atlas.y, source).to_variant()),
+            any_source: layer_glyph(atlas.to_variant()),
+            custom_data: custom_data::<GString>(tile_data, layers.glyph, GLYPH_LAYER)
+                .map(|glyph| glyph.to_string()),
+        }
+        .glyph(atlas.x, atlas.y)
+    }
+}
+
+/// Everywhere a tile's glyph can come from, from the
+/// highest priority to the lowest
+struct GlyphSources {
+    /// The layer's `glyphs` entry for the tile in its atlas source
+    source: Option<String>,
+    /// The layer's `glyphs` entry for the tile in any source
+    any_source: Option<String>,
+    /// The tile set's `glyph` custom data for the tile
+    custom_data: Option<String>,
+}
+
+impl GlyphSources {
+    /// The first usable glyph, falling back to the legacy glyphs
+    /// for the tile at `atlas_x`, `atlas_y`
+    fn glyph(self, atlas_x: i32, atlas_y: i32) -> String {
+        let configured = [self.source, self.any_source, self.custom_data]
+            .into_iter()
+            .flatten()
+            .find(|glyph| is_glyph(glyph));
+        if let Some(glyph) = configured {
+            return glyph;
+        }
+        let legacy = (atlas_y == 0)
+            .then(|| usize::try_from(atlas_x).ok())
+            .flatten()
+            .and_then(|x| LEGACY_GLYPHS.get(x));
+        legacy.unwrap_or(&" ").to_string()
+    }
+}
+
+/// Whether `glyph` is one printable character filling exactly
+/// one cell, since anything wider would push the rest of the
+/// row out of place
+fn is_glyph(glyph: &str) -> bool {
+    let mut chars = glyph.chars();
+    chars
+        .next()
+        .is_some_and(|char| !char.is_control() && chars.next().is_none())
+        && glyph.width() == 1
+}
+
+/// Which custom data layers the tile set has, since asking a
+/// tile for a layer that doesn't exist logs an error
+struct CustomDataLayers {
+    glyph: bool,
+    fg: bool,
+    bg: bool,
+    modifiers: bool,
+}
+
+impl CustomDataLayers {
+    fn of(tile_set: Option<Gd<TileSet>>) -> Self {
+        let has = |name| {
+            tile_set
+                .as_ref()
+                .is_some_and(|tile_set| tile_set.has_custom_data_layer_by_name(name))
+        };
+        Self {
+            glyph: has(GLYPH_LAYER),
+            fg: has(FG_LAYER),
+            bg: has(BG_LAYER),
+            modifiers: has(MODIFIERS_LAYER),
+        }
+    }
+
+    fn any(&self) -> bool {
+        self.glyph || self.fg || self.bg || self.modifiers
+    }
+}
+
+fn custom_data<T: FromGodot>(
+    tile_data: &Option<Gd<TileData>>,
+    present: bool,
+    layer: &str,
+) -> Option<T> {
+    if !present {
+        return None;
+    }
+    tile_data
+        .as_ref()?
+        .get_custom_data(layer)
+        .try_to::<T>()
+        .ok()
+}
+
+/// The tile's own colors and modifiers
+fn tile_style(tile_data: &Option<Gd<TileData>>, layers: &CustomDataLayers) -> Style {
+    let color = |layer, present| {
+        custom_data::<godot::builtin::Color>(tile_data, present, layer)
+            .filter(|color| color.a > 0.0)
+            .map(|color| Color::Rgb(color.r8(), color.g8(), color.b8()))
+    };
+    let mut style = Style::new();
+    if let Some(fg) = color(FG_LAYER, layers.fg) {
+        style = style.fg(fg);
+    }
+    if let Some(bg) = color(BG_LAYER, layers.bg) {
+        style = style.bg(bg);
+    }
+    if let Some(names) = custom_data::<GString>(tile_data, layers.modifiers, MODIFIERS_LAYER) {
+        style = style.add_modifier(modifiers(&names.to_string()));
+    }
+    style
+}
+
+/// Parses modifier names separated by commas or spaces, like
+/// `bold, blink`, ignoring names it doesn't know
+fn modifiers(names: &str) -> Modifier {
+    names
+        .split([',', ' '])
+        .filter(|name| !name.is_empty())
+        .map(|name| match name.to_lowercase().as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underline" | "underlined" => Modifier::UNDERLINED,
+            "blink" | "slow_blink" => Modifier::SLOW_BLINK,
+            "rapid_blink" => Modifier::RAPID_BLINK,
+            "reverse" | "reversed" => Modifier::REVERSED,
+            "hidden" => Modifier::HIDDEN,
+            "strikethrough" | "crossed_out" => Modifier::CROSSED_OUT,
+            _ => Modifier::empty(),
+        })
+        .fold(Modifier::empty(), |modifiers, modifier| {
+            modifiers | modifier
+        })
+}
+
+/// The color a layer's tile is drawn in. Tiles without a color of
+/// their own are drawn in the theme's map color, or the layer's
+/// `modulate` when the theme leaves tile layers their own colors
+pub fn tile_fg(fg: Color, modulate: Color, theme_map: Color) -> Color {
+    match (fg, theme_map) {
+        (Color::Reset, Color::Reset) => modulate,
+        (Color::Reset, theme_map) => theme_map,
+        (fg, _) => fg,
     }
 }
 
-impl Widget for &TerminalTileMapLayer {
-    #[instrument(skip(self, buf))]
+/// A layer, with the theme's map color
+struct ThemedLayer<'a>(&'a TerminalTileMapLayer, Color);
+
+impl Widget for ThemedLayer<'_> {
+    #[instrument(skip_all)]
     fn render(self, mut area: Rect, buf: &mut Buffer)
     where
         Self: Sized,
     {
-        if self.buffer.area.width <= 0 {
+        let ThemedLayer(layer, theme_map) = self;
+        if layer.buffer.area.width == 0 {
             return;
         }
-        let tilemap_line_iterator = self.buffer.content.chunks(self.buffer.area.width as usize);
-        area.x += self.buffer.area.x;
-        area.y += self.buffer.area.y;
-        area.width = self.buffer.area.width.min(area.width - self.buffer.area.x);
-        area.height = self
+        let tilemap_line_iterator = layer
+            .buffer
+            .content
+            .chunks(layer.buffer.area.width as usize);
+        area.x += layer.buffer.area.x;
+        area.y += layer.buffer.area.y;
+        area.width = layer
+            .buffer
+            .area
+            .width
+            .min(area.width - layer.buffer.area.x);
+        area.height = layer
             .buffer
             .area
             .height
-            .min(area.height - self.buffer.area.y);
+            .min(area.height - layer.buffer.area.y);
         for (y, line) in (area.y..area.bottom()).zip(tilemap_line_iterator) {
             let line_start = buf.index_of(area.x, y);
             let target_line = &mut buf.content[line_start..line_start + line.len()];
@@ -91,8 +320,11 @@ impl Widget for &TerminalTileMapLayer {
             target_line
                 .iter_mut()
                 .zip(line.iter())
-                .filter(|(_, line_cell)| line_cell.symbol() != " ")
-                .for_each(|(target_cell, line_cell)| *target_cell = line_cell.clone());
+                .filter(|(_, line_cell)| line_cell.symbol() != " " || line_cell.bg != Color::Reset)
+                .for_each(|(target_cell, line_cell)| {
+                    *target_cell = line_cell.clone();
+                    target_cell.fg = tile_fg(line_cell.fg, layer.modulate, theme_map);
+                });
         }
     }
 }
@@ -145,16 +377,109 @@ impl TerminalTileMap {
             rect.union(layer.bind().buffer.area)
         })
     }
+
+    pub fn bounds(&self) -> MapBounds {
+        MapBounds {
+            left: self.pos_left,
+            top: self.pos_top,
+            right: self.pos_right,
+            bottom: self.pos_bottom,
+        }
+    }
+}
+
+/// The world positions at the edges of a map, used to place
+/// entities on it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MapBounds {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl MapBounds {
+    /// Whether a world position falls inside the area the
+    /// map covers
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let horizontal = self.left.min(self.right)..=self.left.max(self.right);
+        let vertical = self.top.min(self.bottom)..=self.top.max(self.bottom);
+        horizontal.contains(&x) && vertical.contains(&y)
+    }
 }
 
-impl Widget for &TerminalTileMap {
-    #[instrument(skip(self, buf))]
+/// A tile map, with the theme's map color
+pub struct ThemedTileMap<'a>(pub &'a TerminalTileMap, pub Color);
+
+impl Widget for ThemedTileMap<'_> {
+    #[instrument(skip_all)]
     fn render(self, area: Rect, buf: &mut Buffer)
     where
         Self: Sized,
     {
-        for layer in &self.layers {
-            layer.bind().render(area, buf)
+        for layer in &self.0.layers {
+            ThemedLayer(&layer.bind(), self.1).render(area, buf)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Modifier};
+
+    use crate::terminal::tile_map::{GlyphSources, LEGACY_GLYPHS, modifiers, tile_fg};
+
+    fn glyph(source: Option<&str>, any_source: Option<&str>, custom_data: Option<&str>) -> String {
+        GlyphSources {
+            source: source.map(str::to_string),
+            any_source: any_source.map(str::to_string),
+            custom_data: custom_data.map(str::to_string),
         }
+        .glyph(2, 0)
+    }
+
+    #[test]
+    fn theme_only_recolors_tiles_without_their_own_color() {
+        let modulate = Color::Rgb(1, 2, 3);
+        let theme_map = Color::Rgb(4, 5, 6);
+        assert_eq!(tile_fg(Color::Reset, modulate, Color::Reset), modulate);
+        assert_eq!(tile_fg(Color::Reset, modulate, theme_map), theme_map);
+        assert_eq!(tile_fg(Color::Red, modulate, theme_map), Color::Red);
+    }
+
+    #[test]
+    fn glyphs_are_looked_up_in_order() {
+        assert_eq!(glyph(Some("a"), Some("b"), Some("c")), "a");
+        assert_eq!(glyph(None, Some("b"), Some("c")), "b");
+        assert_eq!(glyph(None, None, Some("c")), "c");
+        assert_eq!(glyph(None, None, None), LEGACY_GLYPHS[2]);
+        assert_eq!(
+            GlyphSources {
+                source: None,
+                any_source: None,
+                custom_data: None,
+            }
+            .glyph(2, 1),
+            " "
+        );
+    }
+
+    #[test]
+    fn unusable_glyphs_fall_through() {
+        assert_eq!(glyph(Some(""), Some("b"), None), "b");
+        assert_eq!(glyph(Some("ab"), None, Some("c")), "c");
+        assert_eq!(glyph(Some("中"), Some("\u{301}"), None), LEGACY_GLYPHS[2]);
+        assert_eq!(glyph(Some("\t"), None, None), LEGACY_GLYPHS[2]);
+    }
+
+    #[test]
+    fn parses_modifier_names() {
+        assert_eq!(
+            modifiers("bold, blink"),
+            Modifier::BOLD | Modifier::SLOW_BLINK
+        );
+        assert_eq!(modifiers("Reverse dim"), Modifier::REVERSED | Modifier::DIM);
+        assert_eq!(modifiers("sparkly"), Modifier::empty());
+        assert_eq!(modifiers(""), Modifier::empty());
     }
 }
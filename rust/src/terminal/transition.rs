@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use godot::prelude::*;
+use ratatui::{buffer::Buffer, layout::Rect};
+use tachyonfx::{
+    Effect, Interpolation, IntoEffect,
+    fx::{self, Glitch, IntoTemporaryEffect},
+};
+
+/// How `zoom` animates from one view to the next. Every
+/// transition animates the outgoing view, uncovering the new
+/// one underneath wherever the old one has gone blank
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, Debug)]
+#[godot(via = GString)]
+pub enum ViewTransition {
+    /// Switch views instantly
+    None,
+    /// The old view breaks apart into random cells
+    #[default]
+    Dissolve,
+    /// The old view slides off to the left
+    Slide,
+    /// A line sweeps across, wiping the old view away
+    Sweep,
+    /// The old view glitches out before cutting to the new one
+    Glitch,
+}
+
+impl ViewTransition {
+    /// The effect run over the outgoing view, or `None` if views
+    /// should switch instantly
+    pub fn effect(self, duration_ms: u32) -> Option<Effect> {
+        let effect = match self {
+            ViewTransition::None => return None,
+            ViewTransition::Dissolve => fx::dissolve((duration_ms, Interpolation::QuadOut)),
+            ViewTransition::Slide => fx::effect_fn_buf(
+                (),
+                (duration_ms, Interpolation::CubicInOut),
+                |_, context, buf| {
+                    let shift = (context.alpha() * context.area.width as f32) as u16;
+                    slide_left(buf, context.area, shift);
+                },
+            ),
+            ViewTransition::Sweep => fx::effect_fn_buf(
+                (),
+                (duration_ms, Interpolation::Linear),
+                |_, context, buf| {
+                    let edge = (context.alpha() * context.area.width as f32) as u16;
+                    sweep(buf, context.area, edge);
+                },
+            ),
+            ViewTransition::Glitch => fx::parallel(&[
+                Glitch::builder()
+                    .cell_glitch_ratio(0.4)
+                    .action_start_delay_ms(0..(duration_ms / 2).max(1))
+                    .action_ms(50..150)
+                    .build()
+                    .into_effect()
+                    .with_duration(Duration::from_millis(duration_ms as u64)),
+                fx::dissolve((duration_ms, Interpolation::ExpoIn)),
+            ]),
+        };
+        Some(effect)
+    }
+}
+
+/// The view being zoomed away from, and the effect
+/// animating it out
+pub struct Transition {
+    pub from: String,
+    pub effect: Effect,
+}
+
+/// Shifts every row of `area` left by `shift` columns, leaving
+/// blank cells behind
+fn slide_left(buf: &mut Buffer, area: Rect, shift: u16) {
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = match x.checked_add(shift).filter(|source| *source < area.right()) {
+                Some(source) => buf[(source, y)].clone(),
+                None => Default::default(),
+            };
+            buf[(x, y)] = cell;
+        }
+    }
+}
+
+/// Blanks every column of `area` left of `edge`, drawing the
+/// sweeping line itself at `edge`
+fn sweep(buf: &mut Buffer, area: Rect, edge: u16) {
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let column = x - area.left();
+            if column < edge {
+                buf[(x, y)].reset();
+            } else if column == edge {
+                buf[(x, y)].set_symbol("▌");
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use godot::prelude::*;
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+
+use crate::terminal::tile_map::{MapBounds, TerminalTileMap, ThemedTileMap};
+
+/// A map a view can show. `TerminalTileMap` nodes are the real
+/// thing, but anything that can draw itself can stand in for one,
+/// which keeps drawing the terminal possible without Godot
+pub trait ViewMap {
+    /// The area the map's tiles cover
+    fn used_rect(&self) -> Rect;
+    fn bounds(&self) -> MapBounds;
+    /// Draws the map, with tiles that have no color of their own
+    /// in `theme_map`
+    fn render_map(&self, area: Rect, buf: &mut Buffer, theme_map: Color);
+}
+
+impl ViewMap for Gd<TerminalTileMap> {
+    fn used_rect(&self) -> Rect {
+        self.bind().get_used_rect()
+    }
+
+    fn bounds(&self) -> MapBounds {
+        self.bind().bounds()
+    }
+
+    fn render_map(&self, area: Rect, buf: &mut Buffer, theme_map: Color) {
+        ThemedTileMap(&self.bind(), theme_map).render(area, buf);
+    }
+}
+
+/// Every map the terminal can show, discovered from the
+/// `TerminalTileMap` children of the terminal node. Views are
+/// named after their node in kebab case, so a `Room1` node is
+/// zoomed into with `zoom room1`
+#[derive(Default)]
+pub struct Views {
+    views: Vec<(String, Box<dyn ViewMap>)>,
+    main: String,
+    current: String,
+}
+
+impl Views {
+    /// Registers every `TerminalTileMap` child of `parent`. The
+    /// view named `main` is the unzoomed one, falling back to the
+    /// first view found
+    pub fn discover(parent: &Gd<Node>, main: &str) -> Self {
+        let mut views: Vec<(String, Box<dyn ViewMap>)> = Vec::new();
+        for tile_map in parent
+            .get_children()
+            .iter_shared()
+            .flat_map(|node| node.try_cast::<TerminalTileMap>())
+        {
+            let name = view_name(&tile_map.get_name().to_string());
+            if views.iter().any(|(existing, _)| *existing == name) {
+                tracing::warn!("Two views are named {name}, ignoring the second");
+                continue;
+            }
+            views.push((name, Box::new(tile_map)));
+        }
+        Self::new(views, main)
+    }
+
+    /// Uses `views`, named as they would be found by `discover`
+    pub fn new(views: Vec<(String, Box<dyn ViewMap>)>, main: &str) -> Self {
+        let main = view_name(main);
+        let main = if views.iter().any(|(name, _)| *name == main) {
+            main
+        } else {
+            views
+                .first()
+                .map(|(name, _)| name.clone())
+                .unwrap_or_default()
+        };
+        Self {
+            views,
+            current: main.clone(),
+            main,
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.views.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names().any(|view| view == name)
+    }
+
+    pub fn current(&self) -> &str {
+        &self.current
+    }
+
+    pub fn tile_map(&self, name: &str) -> Option<&dyn ViewMap> {
+        self.views
+            .iter()
+            .find(|(view, _)| view == name)
+            .map(|(_, tile_map)| tile_map.as_ref())
+    }
+
+    pub fn is_main(&self, name: &str) -> bool {
+        self.main == name
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        !self.is_main(&self.current)
+    }
+
+    /// Switches to the view called `name`, returning `false` if
+    /// it was already being shown
+    pub fn set_current(&mut self, name: &str) -> bool {
+        if self.current == name {
+            return false;
+        }
+        self.current = name.to_string();
+        true
+    }
+}
+
+/// Converts a node name like `MainView` into `main-view`
+fn view_name(node_name: &str) -> String {
+    let mut name = String::with_capacity(node_name.len());
+    let mut previous: Option<char> = None;
+    for character in node_name.chars() {
+        if character.is_uppercase()
+            && previous.is_some_and(|previous| previous.is_lowercase() || previous.is_ascii_digit())
+        {
+            name.push('-');
+        }
+        if character == '_' || character == ' ' {
+            name.push('-');
+        } else {
+            name.extend(character.to_lowercase());
+        }
+        previous = Some(character);
+    }
+    name
+}
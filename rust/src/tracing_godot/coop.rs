@@ -0,0 +1,324 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::{IpAddr, SocketAddr, TcpListener},
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, Sender},
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{
+    Router,
+    extract::{
+        ConnectInfo, State, WebSocketUpgrade,
+        ws::{self, WebSocket},
+    },
+    http::{HeaderMap, StatusCode},
+    response::{Html, IntoResponse},
+    routing::get,
+};
+use ratatui::buffer::Buffer;
+use tokio::sync::broadcast;
+
+use crate::tracing_godot::{
+    is_same_origin, script,
+    spectator::{Spectator, push_json_string},
+    stylesheet,
+};
+
+/// Wrong codes an address can enter before it has to wait
+/// between attempts
+const FREE_ATTEMPTS: u32 = 3;
+/// How long an address waits after its first wrong code past the
+/// free ones. Every wrong code after that doubles the wait, up
+/// to `MAX_LOCKOUT`
+const FIRST_LOCKOUT: Duration = Duration::from_secs(5);
+const MAX_LOCKOUT: Duration = Duration::from_secs(300);
+
+/// Serves a page a second player can type commands into the
+/// terminal from, once they enter the session code. The page
+/// shows the terminal and the output of their commands
+pub(crate) struct CoopServer {
+    coop: Arc<Coop>,
+    handle: axum_server::Handle,
+}
+
+impl CoopServer {
+    /// Starts serving on `address`, failing if it can't be bound
+    pub(crate) fn bind(address: &str, session_code: String) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let coop = Arc::new(Coop::new(session_code));
+        let handle = axum_server::Handle::new();
+        std::thread::spawn({
+            let coop = coop.clone();
+            let handle = handle.clone();
+            move || serve(listener, handle, coop)
+        });
+        Ok(Self { coop, handle })
+    }
+
+    /// Shows a frame the terminal drew to the second player
+    pub(crate) fn send_frame(&self, buffer: &Buffer) {
+        self.coop.spectator.send_frame(buffer);
+    }
+
+    /// Shows the output of one of the second player's commands.
+    /// ANSI escapes are left out
+    pub(crate) fn send_output(&self, output: &str, error: bool) {
+        let mut message = String::from(r#"{"output":"#);
+        push_json_string(
+            &mut message,
+            &anstream::adapter::strip_str(output).to_string(),
+        );
+        message.push_str(&format!(r#","error":{error}}}"#));
+        let _ = self.coop.output_sender.send(message.into());
+    }
+
+    /// Every command the second player sent since the last call
+    pub(crate) fn take_commands(&self) -> Vec<String> {
+        self.coop
+            .command_receiver
+            .lock()
+            .unwrap()
+            .try_iter()
+            .collect()
+    }
+}
+
+impl Drop for CoopServer {
+    fn drop(&mut self) {
+        self.handle.shutdown();
+    }
+}
+
+struct Coop {
+    session_code: String,
+    throttle: Mutex<Throttle>,
+    spectator: Spectator,
+    output_sender: broadcast::Sender<Arc<str>>,
+    command_sender: Sender<String>,
+    command_receiver: Mutex<Receiver<String>>,
+}
+
+impl Coop {
+    fn new(session_code: String) -> Self {
+        let (output_sender, _) = broadcast::channel(16);
+        let (command_sender, command_receiver) = mpsc::channel();
+        Self {
+            session_code,
+            throttle: Mutex::new(Throttle::default()),
+            spectator: Spectator::new(),
+            output_sender,
+            command_sender,
+            command_receiver: Mutex::new(command_receiver),
+        }
+    }
+
+    /// Checks a code `address` entered, refusing it without
+    /// looking if the address has to wait after too many wrong ones
+    fn check_session_code(&self, address: IpAddr, session_code: &str) -> Result<(), String> {
+        let mut throttle = self.throttle.lock().unwrap();
+        let now = Instant::now();
+        if let Some(wait) = throttle.wait(address, now) {
+            return Err(format!(
+                "Too many wrong codes, try again in {}s",
+                wait.as_secs() + 1
+            ));
+        }
+        if self.session_code.eq_ignore_ascii_case(session_code.trim()) {
+            throttle.succeed(address);
+            Ok(())
+        } else {
+            throttle.fail(address, now);
+            Err("Wrong session code".to_string())
+        }
+    }
+}
+
+/// Tracks wrong codes per address, so codes can't be guessed
+/// faster than a few a minute
+#[derive(Default)]
+struct Throttle {
+    addresses: HashMap<IpAddr, Attempts>,
+}
+
+#[derive(Default)]
+struct Attempts {
+    failures: u32,
+    locked_until: Option<Instant>,
+}
+
+impl Throttle {
+    /// How long `address` still has to wait before its next attempt
+    fn wait(&self, address: IpAddr, now: Instant) -> Option<Duration> {
+        self.addresses
+            .get(&address)?
+            .locked_until
+            .filter(|locked_until| *locked_until > now)
+            .map(|locked_until| locked_until - now)
+    }
+
+    fn fail(&mut self, address: IpAddr, now: Instant) {
+        let attempts = self.addresses.entry(address).or_default();
+        attempts.failures += 1;
+        if let Some(doublings) = attempts.failures.checked_sub(FREE_ATTEMPTS + 1) {
+            let lockout = FIRST_LOCKOUT
+                .saturating_mul(1 << doublings.min(16))
+                .min(MAX_LOCKOUT);
+            attempts.locked_until = Some(now + lockout);
+        }
+    }
+
+    fn succeed(&mut self, address: IpAddr) {
+        self.addresses.remove(&address);
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn serve(listener: TcpListener, handle: axum_server::Handle, coop: Arc<Coop>) {
+    let app = Router::new()
+        .route("/", get(async || Html(include_str!("coop.html"))))
+        .route("/screen.css", get(stylesheet))
+        .route("/screen.js", get(script))
+        .route("/ws", get(coop_ws))
+        .with_state(coop);
+    if let Err(e) = axum_server::from_tcp(listener)
+        .handle(handle)
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+    {
+        tracing::warn!("Co-op server stopped: {e}");
+    }
+}
+
+async fn coop_ws(
+    State(coop): State<Arc<Coop>>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> axum::response::Response {
+    if !is_same_origin(&headers) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    ws.on_upgrade(move |ws| handle_coop_socket(ws, coop, address.ip()))
+}
+
+/// The first message has to be the session code, every message
+/// after it is a command line
+async fn handle_coop_socket(mut socket: WebSocket, coop: Arc<Coop>, address: IpAddr) {
+    let Some(Ok(ws::Message::Text(session_code))) = socket.recv().await else {
+        return;
+    };
+    if let Err(reason) = coop.check_session_code(address, &session_code) {
+        let mut message = String::from(r#"{"refused":"#);
+        push_json_string(&mut message, &reason);
+        message.push('}');
+        let _ = socket.send(ws::Message::Text(message.into())).await;
+        return;
+    }
+    let mut output_receiver = coop.output_sender.subscribe();
+    let (screen, mut frame_receiver) = coop.spectator.subscribe();
+    for message in [r#"{"joined":true}"#, &*screen] {
+        if socket
+            .send(ws::Message::Text(message.into()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+    tracing::info!("Co-op player joined from {address}");
+    loop {
+        tokio::select! {
+            frame = frame_receiver.recv() => {
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        frame_receiver = frame_receiver.resubscribe();
+                        coop.spectator.snapshot()
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if socket.send(ws::Message::Text(frame.as_ref().into())).await.is_err() {
+                    break;
+                }
+            }
+            output = output_receiver.recv() => {
+                let output = match output {
+                    Ok(output) => output,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if socket.send(ws::Message::Text(output.as_ref().into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(ws::Message::Text(command))) => {
+                    for line in command.lines().filter(|line| !line.trim().is_empty()) {
+                        let _ = coop.command_sender.send(line.to_string());
+                    }
+                }
+                Some(Ok(ws::Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+    tracing::info!("Co-op player left");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        time::{Duration, Instant},
+    };
+
+    use crate::tracing_godot::coop::{FIRST_LOCKOUT, FREE_ATTEMPTS, MAX_LOCKOUT, Throttle};
+
+    const ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2));
+    const OTHER_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 3));
+
+    #[test]
+    fn locks_out_after_the_free_attempts() {
+        let mut throttle = Throttle::default();
+        let now = Instant::now();
+        for _ in 0..FREE_ATTEMPTS {
+            throttle.fail(ADDRESS, now);
+            assert_eq!(throttle.wait(ADDRESS, now), None);
+        }
+        throttle.fail(ADDRESS, now);
+        assert_eq!(throttle.wait(ADDRESS, now), Some(FIRST_LOCKOUT));
+        assert_eq!(throttle.wait(OTHER_ADDRESS, now), None);
+        assert_eq!(throttle.wait(ADDRESS, now + FIRST_LOCKOUT), None);
+    }
+
+    #[test]
+    fn lockouts_double_up_to_the_maximum() {
+        let mut throttle = Throttle::default();
+        let now = Instant::now();
+        for _ in 0..=FREE_ATTEMPTS + 1 {
+            throttle.fail(ADDRESS, now);
+        }
+        assert_eq!(throttle.wait(ADDRESS, now), Some(FIRST_LOCKOUT * 2));
+        for _ in 0..20 {
+            throttle.fail(ADDRESS, now);
+        }
+        assert_eq!(throttle.wait(ADDRESS, now), Some(MAX_LOCKOUT));
+    }
+
+    #[test]
+    fn the_right_code_resets_the_count() {
+        let mut throttle = Throttle::default();
+        let now = Instant::now();
+        for _ in 0..FREE_ATTEMPTS {
+            throttle.fail(ADDRESS, now);
+        }
+        throttle.succeed(ADDRESS);
+        throttle.fail(ADDRESS, now + Duration::from_secs(1));
+        assert_eq!(throttle.wait(ADDRESS, now), None);
+    }
+}
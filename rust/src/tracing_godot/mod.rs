@@ -13,18 +13,24 @@ use axum::{
         State, WebSocketUpgrade,
         ws::{self, WebSocket},
     },
-    http::{HeaderValue, Method},
-    response::Html,
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    response::{Html, IntoResponse},
     routing::get,
 };
 use futures_util::TryFutureExt;
 use godot::{classes::Object, prelude::*};
+use ratatui::buffer::Buffer;
 use tokio::sync::{Mutex, broadcast};
 use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing::{Level, dispatcher, span};
 use tracing_subscriber::fmt::MakeWriter;
 
-use crate::LOG_SERVER;
+use crate::{LOG_SERVER, tracing_godot::spectator::Spectator};
+
+mod coop;
+mod spectator;
+
+pub(crate) use coop::CoopServer;
 
 #[derive(GodotClass)]
 #[class(init, base=Object)]
@@ -143,6 +149,7 @@ impl Write for LogServerWriter {
 pub(crate) struct LogServer {
     handle: axum_server::Handle,
     log_sender: broadcast::Sender<LogServerEvent>,
+    spectator: Arc<Spectator>,
     join_handle: JoinHandle<()>,
 }
 
@@ -156,14 +163,17 @@ impl LogServer {
     pub(crate) fn new() -> Self {
         let handle = axum_server::Handle::new();
         let (log_sender, log_receiver) = broadcast::channel(10);
+        let spectator = Arc::new(Spectator::new());
         let join_handle = std::thread::spawn({
             let handle = handle.clone();
             let log_sender = log_sender.clone();
-            move || start_log_server(handle, log_sender, log_receiver)
+            let spectator = spectator.clone();
+            move || start_log_server(handle, log_sender, log_receiver, spectator)
         });
         Self {
             handle,
             log_sender,
+            spectator,
             join_handle,
         }
     }
@@ -178,6 +188,12 @@ impl LogServer {
     pub(crate) fn send(&self, event: LogServerEvent) {
         let _ = self.log_sender.send(event);
     }
+
+    /// Shows a frame the terminal drew to anyone watching
+    /// `/spectate`
+    pub(crate) fn send_frame(&self, buffer: &Buffer) {
+        self.spectator.send_frame(buffer);
+    }
 }
 
 #[derive(Clone)]
@@ -185,17 +201,20 @@ struct LogServerState {
     log_sender: broadcast::Sender<LogServerEvent>,
     port_serial: Arc<AtomicU16>,
     first_receiver: Arc<Mutex<Option<broadcast::Receiver<LogServerEvent>>>>,
+    spectator: Arc<Spectator>,
 }
 
 impl LogServerState {
     fn new(
         log_sender: broadcast::Sender<LogServerEvent>,
         log_receiver: broadcast::Receiver<LogServerEvent>,
+        spectator: Arc<Spectator>,
     ) -> Self {
         Self {
             first_receiver: Arc::new(Mutex::new(Some(log_receiver))),
             log_sender,
             port_serial: Arc::new(8001.into()),
+            spectator,
         }
     }
 }
@@ -205,12 +224,20 @@ pub(crate) async fn start_log_server(
     handle: axum_server::Handle,
     log_sender: broadcast::Sender<LogServerEvent>,
     log_receiver: broadcast::Receiver<LogServerEvent>,
+    spectator: Arc<Spectator>,
 ) {
     let app = Router::new()
         .route("/", get(async || Html(include_str!("log_server.html"))))
         .route("/ping", get(async || "pong"))
         .route("/new_instance", get(new_instance))
         .route("/ws", get(log_ws))
+        .route(
+            "/spectate",
+            get(async || Html(include_str!("spectator.html"))),
+        )
+        .route("/spectate/ws", get(spectate_ws))
+        .route("/screen.css", get(stylesheet))
+        .route("/screen.js", get(script))
         .layer(
             CorsLayer::new()
                 .allow_methods([Method::GET])
@@ -218,7 +245,7 @@ pub(crate) async fn start_log_server(
                     "http://localhost:8000",
                 ))),
         )
-        .with_state(LogServerState::new(log_sender, log_receiver));
+        .with_state(LogServerState::new(log_sender, log_receiver, spectator));
 
     let socket_addr = if let Ok(response) = reqwest::get("http://localhost:8000/new_instance")
         .and_then(|resp| resp.text())
@@ -282,13 +309,13 @@ async fn handle_socket(
                         match log_event {
                             LogServerEvent::Log(log) => {
                                 let html = ansi_to_html::convert(&log).unwrap();
-                                if let Err(_) = socket.send(ws::Message::Text(html.into())).await {
+                                if socket.send(ws::Message::Text(html.into())).await.is_err() {
                                     return;
                                 };
                             }
                             LogServerEvent::NewInstance(port) => {
                                 let port = port.to_le_bytes();
-                                if let Err(_) = socket.send(ws::Message::Binary(Bytes::from_owner(port))).await {
+                                if socket.send(ws::Message::Binary(Bytes::from_owner(port))).await.is_err() {
                                     return;
                                 };
                             }
@@ -296,7 +323,7 @@ async fn handle_socket(
                     }
                     Err(broadcast::error::RecvError::Lagged(missed_logs)) => {
                         let missed_log_message = format!("Uh oh, you missed {} logs", missed_logs);
-                        if let Err(_) = socket.send(ws::Message::Text(missed_log_message.into())).await {
+                        if socket.send(ws::Message::Text(missed_log_message.into())).await.is_err() {
                             return;
                         };
                     }
@@ -311,3 +338,82 @@ async fn handle_socket(
         }
     }
 }
+
+async fn spectate_ws(
+    State(log_server_state): State<LogServerState>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> axum::response::Response {
+    if !is_same_origin(&headers) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    ws.on_upgrade(move |ws| handle_spectator_socket(ws, log_server_state.spectator))
+}
+
+async fn handle_spectator_socket(mut socket: WebSocket, spectator: Arc<Spectator>) {
+    let (screen, mut frame_receiver) = spectator.subscribe();
+    if socket
+        .send(ws::Message::Text(screen.as_ref().into()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+    loop {
+        tokio::select! {
+            frame = frame_receiver.recv() => {
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    // Diffs were missed, so skip the ones still queued
+                    // and start over from the whole screen
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        frame_receiver = frame_receiver.resubscribe();
+                        spectator.snapshot()
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                if socket.send(ws::Message::Text(frame.as_ref().into())).await.is_err() {
+                    return;
+                }
+            }
+            msg = socket.recv() => {
+                if msg.and_then(|m| m.ok()).is_none() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Browsers send the origin of the page opening a websocket, so
+/// refusing other origins keeps pages the player visits from
+/// connecting in their name. Clients that aren't browsers send
+/// no origin and are let through
+fn is_same_origin(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let (Ok(origin), Some(Ok(host))) = (
+        origin.to_str(),
+        headers.get(header::HOST).map(HeaderValue::to_str),
+    ) else {
+        return false;
+    };
+    origin.strip_prefix("http://") == Some(host)
+}
+
+/// The palette and layout shared by the spectator and co-op pages
+async fn stylesheet() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/css")],
+        include_str!("screen.css"),
+    )
+}
+
+/// Draws spectator frames, shared by the spectator and co-op pages
+async fn script() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/javascript")],
+        include_str!("screen.js"),
+    )
+}
@@ -0,0 +1,55 @@
+// Draws the frames `Spectator` sends, see spectator.rs for their format
+
+// Modifier bits, matching ratatui's `Modifier`
+const BOLD = 1, DIM = 2, ITALIC = 4, UNDERLINED = 8, SLOW_BLINK = 16, RAPID_BLINK = 32, REVERSED = 64, HIDDEN = 128, CROSSED_OUT = 256;
+
+// Returns a function that draws a frame into `screen`
+function createScreen(screen) {
+  let cells = [];
+  let width = 0;
+
+  function resize(newWidth, newHeight) {
+    screen.replaceChildren();
+    cells = [];
+    width = newWidth;
+    for (let y = 0; y < newHeight; y++) {
+      const row = document.createElement('div');
+      for (let x = 0; x < newWidth; x++) {
+        const cell = document.createElement('span');
+        row.appendChild(cell);
+        cells.push(cell);
+      }
+      screen.appendChild(row);
+    }
+  }
+
+  function draw([x, y, symbol, fg, bg, modifiers]) {
+    const cell = cells[y * width + x];
+    if (cell === undefined) {
+      return;
+    }
+    if (modifiers & REVERSED) {
+      [fg, bg] = [bg || 'var(--background)', fg || 'var(--foreground)'];
+    }
+    cell.textContent = symbol;
+    cell.style.color = fg;
+    cell.style.backgroundColor = bg;
+    cell.style.fontWeight = modifiers & BOLD ? 'bold' : '';
+    cell.style.opacity = modifiers & DIM ? '0.6' : '';
+    cell.style.fontStyle = modifiers & ITALIC ? 'italic' : '';
+    cell.style.visibility = modifiers & HIDDEN ? 'hidden' : '';
+    cell.style.animation = modifiers & RAPID_BLINK ? 'blink 0.4s step-end infinite'
+      : modifiers & SLOW_BLINK ? 'blink 1s step-end infinite' : '';
+    cell.style.textDecoration = [
+      modifiers & UNDERLINED ? 'underline' : '',
+      modifiers & CROSSED_OUT ? 'line-through' : '',
+    ].join(' ').trim();
+  }
+
+  return (frame) => {
+    if (frame.full) {
+      resize(frame.width, frame.height);
+    }
+    frame.cells.forEach(draw);
+  };
+}
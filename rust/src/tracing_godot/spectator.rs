@@ -0,0 +1,147 @@
+use std::{
+    fmt::Write,
+    sync::{Arc, Mutex},
+};
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::Rect,
+    style::Color,
+};
+use tokio::sync::broadcast;
+
+use crate::palette::{LOG_PAGE, ansi_index, to_rgb};
+
+/// Keeps the last frame the terminal drew so spectators can
+/// watch it from a browser. Frames are sent as JSON:
+///
+/// ```json
+/// {"width":80,"height":24,"full":false,"cells":[[x,y,"symbol","fg","bg",modifiers]]}
+/// ```
+///
+/// A `full` frame lists every cell, otherwise only the cells
+/// that changed since the last frame are listed. Colors are CSS
+/// colors, or empty for the default color, and modifiers are
+/// ratatui's `Modifier` bits
+pub(crate) struct Spectator {
+    screen: Mutex<Buffer>,
+    frame_sender: broadcast::Sender<Arc<str>>,
+}
+
+impl Spectator {
+    pub(crate) fn new() -> Self {
+        let (frame_sender, _) = broadcast::channel(16);
+        Self {
+            screen: Mutex::new(Buffer::empty(Rect::ZERO)),
+            frame_sender,
+        }
+    }
+
+    /// Shares a frame the terminal just drew with every spectator
+    pub(crate) fn send_frame(&self, buffer: &Buffer) {
+        let mut screen = self.screen.lock().unwrap();
+        if self.frame_sender.receiver_count() > 0 {
+            let frame = if screen.area != buffer.area {
+                encode_full(buffer)
+            } else {
+                let updates = screen.diff(buffer);
+                if updates.is_empty() {
+                    return;
+                }
+                encode(buffer.area, false, updates.into_iter())
+            };
+            let _ = self.frame_sender.send(frame.into());
+        }
+        screen.clone_from(buffer);
+    }
+
+    /// The whole screen as it is now, and a receiver for
+    /// every frame after it
+    pub(crate) fn subscribe(&self) -> (Arc<str>, broadcast::Receiver<Arc<str>>) {
+        let screen = self.screen.lock().unwrap();
+        (encode_full(&screen).into(), self.frame_sender.subscribe())
+    }
+
+    pub(crate) fn snapshot(&self) -> Arc<str> {
+        encode_full(&self.screen.lock().unwrap()).into()
+    }
+}
+
+fn encode_full(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    encode(
+        area,
+        true,
+        area.positions()
+            .map(|position| (position.x, position.y, &buffer[position])),
+    )
+}
+
+fn encode<'a>(area: Rect, full: bool, cells: impl Iterator<Item = (u16, u16, &'a Cell)>) -> String {
+    let mut frame = format!(
+        r#"{{"width":{},"height":{},"full":{full},"cells":["#,
+        area.width, area.height
+    );
+    for (index, (x, y, cell)) in cells.enumerate() {
+        if index > 0 {
+            frame.push(',');
+        }
+        let _ = write!(frame, "[{},{},", x - area.x, y - area.y);
+        push_json_string(&mut frame, cell.symbol());
+        let _ = write!(
+            frame,
+            r#","{}","{}",{}]"#,
+            css_color(cell.fg),
+            css_color(cell.bg),
+            cell.modifier.bits()
+        );
+    }
+    frame.push_str("]}");
+    frame
+}
+
+pub(super) fn push_json_string(frame: &mut String, string: &str) {
+    frame.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => frame.push_str("\\\""),
+            '\\' => frame.push_str("\\\\"),
+            char if char.is_control() => {
+                let _ = write!(frame, "\\u{:04x}", char as u32);
+            }
+            char => frame.push(char),
+        }
+    }
+    frame.push('"');
+}
+
+/// The names `screen.css` gives the 16 ANSI colors
+const CSS_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// Matches the palette the log page uses
+fn css_color(color: Color) -> String {
+    if let Some(index) = ansi_index(color) {
+        return format!("var(--{})", CSS_NAMES[index as usize]);
+    }
+    match to_rgb(color, &LOG_PAGE) {
+        Some((r, g, b)) => format!("#{r:02x}{g:02x}{b:02x}"),
+        None => String::new(),
+    }
+}